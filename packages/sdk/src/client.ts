@@ -1,10 +1,10 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, type Idl, AnchorProvider } from "@coral-xyz/anchor";
-import { Connection, PublicKey, SystemProgram, Keypair, ComputeBudgetProgram } from "@solana/web3.js";
+import { Connection, PublicKey, SystemProgram, Keypair, ComputeBudgetProgram, type AccountMeta } from "@solana/web3.js";
 import { getAssociatedTokenAddressSync, createAssociatedTokenAccountIdempotentInstruction, ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { BN } from "bn.js";
 import idl from "./idl/fuin.json";
-import { findDelegatePda, findVaultPda, METEORA_DLMM_PROGRAM } from "./pda";
+import { findContributionPda, findDelegatePda, findSchedulePda, findStreamPda, findVaultPda, MEMO_PROGRAM_ID, METEORA_DLMM_PROGRAM } from "./pda";
 
 // Devnet Pyth Feed for SOL/USD (Hardcoded for now)
 export const PYTH_SOL_FEED_ID = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
@@ -88,6 +88,12 @@ export class FuinClient {
     this.connection = connection;
   }
 
+  /** The vault's registered spend ledger, which every execution has to pass once set */
+  private async spendLedgerOf(vault: PublicKey): Promise<PublicKey | null> {
+    const account = await (this.program.account as any).vault.fetch(vault);
+    return account.spendLedger ?? null;
+  }
+
   // --- GUARDIAN ACTIONS ---
 
   async createVault(nonce: number, dailyCapSol: number, perTxCapSol: number, allowedPrograms: PublicKey[] = []) {
@@ -152,7 +158,8 @@ export class FuinClient {
     delegateNonce: number,
     destination: PublicKey,
     amountSol: number,
-    signer: Keypair
+    signer: Keypair,
+    memo: string | null = null
   ) {
     const bnVaultNonce = new BN(vaultNonce);
     const bnDelegateNonce = new BN(delegateNonce);
@@ -163,7 +170,7 @@ export class FuinClient {
     const amount = new BN(amountSol * 1_000_000_000);
 
     const tx = await this.program.methods
-      .executeTransfer!(bnVaultNonce, bnDelegateNonce, amount)
+      .executeTransfer!(bnVaultNonce, bnDelegateNonce, amount, memo)
       .accounts({
         relayer: signer.publicKey,
         delegateKey: signer.publicKey,
@@ -172,6 +179,8 @@ export class FuinClient {
         delegate: delegatePda,
         destination: destination,
         systemProgram: SystemProgram.programId,
+        memoProgram: memo ? MEMO_PROGRAM_ID : null,
+        spendLedger: await this.spendLedgerOf(vaultPda),
       })
       .signers([signer])
       .rpc();
@@ -184,7 +193,8 @@ export class FuinClient {
     vaultNonce: number,
     delegateNonce: number,
     destination: PublicKey,
-    amountSol: number
+    amountSol: number,
+    memo: string | null = null
   ) {
     const bnVaultNonce = new BN(vaultNonce);
     const bnDelegateNonce = new BN(delegateNonce);
@@ -196,7 +206,7 @@ export class FuinClient {
     const walletKey = this.program.provider.publicKey!;
 
     const tx = await this.program.methods
-      .executeTransfer!(bnVaultNonce, bnDelegateNonce, amount)
+      .executeTransfer!(bnVaultNonce, bnDelegateNonce, amount, memo)
       .accounts({
         relayer: walletKey,
         delegateKey: walletKey,
@@ -205,7 +215,50 @@ export class FuinClient {
         delegate: delegatePda,
         destination: destination,
         systemProgram: SystemProgram.programId,
+        memoProgram: memo ? MEMO_PROGRAM_ID : null,
+        spendLedger: await this.spendLedgerOf(vaultPda),
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  /** Pays several SOL recipients in one instruction, all or nothing */
+  async transferSolBatch(
+    guardian: PublicKey,
+    vaultNonce: number,
+    delegateNonce: number,
+    payments: { destination: PublicKey; amountSol: number }[],
+    signer: Keypair
+  ) {
+    const bnVaultNonce = new BN(vaultNonce);
+    const bnDelegateNonce = new BN(delegateNonce);
+
+    const [vaultPda] = findVaultPda(guardian, bnVaultNonce, this.program.programId);
+    const [delegatePda] = findDelegatePda(vaultPda, bnDelegateNonce, this.program.programId);
+
+    const items = payments.map(({ amountSol }) => ({
+      sol: { amount: new BN(amountSol * 1_000_000_000) },
+    }));
+    const destinations = payments.map(({ destination }) => ({
+      pubkey: destination,
+      isSigner: false,
+      isWritable: true,
+    }));
+
+    const tx = await this.program.methods
+      .executeBatch!(bnVaultNonce, bnDelegateNonce, items)
+      .accounts({
+        relayer: signer.publicKey,
+        delegateKey: signer.publicKey,
+        guardian: guardian,
+        vault: vaultPda,
+        delegate: delegatePda,
+        tokenProgram: null,
+        spendLedger: await this.spendLedgerOf(vaultPda),
       })
+      .remainingAccounts(destinations)
+      .signers([signer])
       .rpc();
 
     return tx;
@@ -219,8 +272,13 @@ export class FuinClient {
     destination: PublicKey,
     amountTokens: number,
     signer: Keypair,
-    feedId: string = PYTH_SOL_FEED_ID,
-    feedAccount: PublicKey = PYTH_SOL_FEED_ACCOUNT
+    // Pass null for both when the mint is on the vault's pegged list without a depeg guard (max_depeg_bps 0),
+    // a guarded pegged mint still needs its oracle
+    feedId: string | null = PYTH_SOL_FEED_ID,
+    feedAccount: PublicKey | null = PYTH_SOL_FEED_ACCOUNT,
+    memo: string | null = null,
+    // Extra account metas for Token-2022 transfer hook mints
+    transferHookAccounts: AccountMeta[] = []
   ) {
     const bnVaultNonce = new BN(vaultNonce);
     const bnDelegateNonce = new BN(delegateNonce);
@@ -239,7 +297,7 @@ export class FuinClient {
     );
 
     const tx = await this.program.methods
-      .executeSplTransfer!(bnVaultNonce, bnDelegateNonce, new BN(amountTokens), feedId)
+      .executeSplTransfer!(bnVaultNonce, bnDelegateNonce, new BN(amountTokens), feedId, memo)
       .accounts({
         relayer: signer.publicKey,
         delegateKey: signer.publicKey,
@@ -251,7 +309,10 @@ export class FuinClient {
         mint: mint,
         tokenProgram: TOKEN_PROGRAM_ID,
         priceUpdate: feedAccount,
+        memoProgram: memo ? MEMO_PROGRAM_ID : null,
+        spendLedger: await this.spendLedgerOf(vaultPda),
       })
+      .remainingAccounts(transferHookAccounts)
       .preInstructions([createDestAtaIx])
       .signers([signer])
       .rpc();
@@ -339,10 +400,14 @@ export class FuinClient {
         tokenYMint: dlmmPool.lbPair.tokenYMint,
         oracle: oracle,
         tokenInMint: inputMint,
+        tokenOutMint: outputMint,
+        tokenOutProgram: TOKEN_PROGRAM_ID,
         tokenXProgram: TOKEN_PROGRAM_ID,
         tokenYProgram: TOKEN_PROGRAM_ID,
-        eventAuthority: eventAuthority,
+        dlmmEventAuthority: eventAuthority,
         dlmmProgram: METEORA_DLMM_PROGRAM,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        spendLedger: await this.spendLedgerOf(vaultPda),
       } as any)
       .remainingAccounts(binArrayAccountMetas)
       .preInstructions([
@@ -386,6 +451,43 @@ export class FuinClient {
     return tx;
   }
 
+  // Upgrades a vault created before the current account layout, the guardian pays the extra rent
+  async migrateVault(nonce: number) {
+    const bnNonce = new BN(nonce);
+    const [vaultPda] = findVaultPda(this.program.provider.publicKey!, bnNonce, this.program.programId);
+
+    const tx = await this.program.methods
+      .migrateVault!(bnNonce)
+      .accounts({
+        guardian: this.program.provider.publicKey!,
+        vault: vaultPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  // Upgrades a delegate issued before the current account layout, its vault has to be migrated first
+  async migrateDelegate(vaultNonce: number, delegateNonce: number) {
+    const bnVaultNonce = new BN(vaultNonce);
+    const bnDelegateNonce = new BN(delegateNonce);
+    const [vaultPda] = findVaultPda(this.program.provider.publicKey!, bnVaultNonce, this.program.programId);
+    const [delegatePda] = findDelegatePda(vaultPda, bnDelegateNonce, this.program.programId);
+
+    const tx = await this.program.methods
+      .migrateDelegate!(bnVaultNonce, bnDelegateNonce)
+      .accounts({
+        guardian: this.program.provider.publicKey!,
+        vault: vaultPda,
+        delegate: delegatePda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return tx;
+  }
+
   async delegateControl(vaultNonce: number, delegateNonce: number, status: number) {
     const bnVaultNonce = new BN(vaultNonce);
     const bnDelegateNonce = new BN(delegateNonce);
@@ -411,15 +513,31 @@ export class FuinClient {
     newPerTxCapSol?: number,
     newAllowList?: PublicKey[],
     newDenyList?: PublicKey[],
+    newUsesPerBatch?: number,
+    // USD caps on oracle-valued SPL transfers and swaps, 0 = unlimited
+    newDailyCapUsd?: number,
+    newPerTxCapUsd?: number,
   ) {
     const bnNonce = new BN(nonce);
     const [vaultPda] = findVaultPda(this.program.provider.publicKey!, bnNonce, this.program.programId);
 
     const newDailyCap = newDailyCapSol !== undefined ? new BN(newDailyCapSol * 1_000_000_000) : null;
     const newPerTxCap = newPerTxCapSol !== undefined ? new BN(newPerTxCapSol * 1_000_000_000) : null;
+    // micro-USD on chain
+    const newDailyUsdCap = newDailyCapUsd !== undefined ? new BN(Math.round(newDailyCapUsd * 1_000_000)) : null;
+    const newPerTxUsdCap = newPerTxCapUsd !== undefined ? new BN(Math.round(newPerTxCapUsd * 1_000_000)) : null;
 
     const tx = await this.program.methods
-      .updateVault!(bnNonce, newDailyCap, newPerTxCap, newAllowList ?? null, newDenyList ?? null)
+      .updateVault!(
+        bnNonce,
+        newDailyCap,
+        newPerTxCap,
+        newAllowList ?? null,
+        newDenyList ?? null,
+        newUsesPerBatch ?? null,
+        newDailyUsdCap,
+        newPerTxUsdCap,
+      )
       .accounts({
         guardian: this.program.provider.publicKey!,
         vault: vaultPda,
@@ -444,4 +562,306 @@ export class FuinClient {
 
     return tx;
   }
+
+  async withdrawSpl(nonce: number, mint: PublicKey, amountTokens: number, tokenProgram: PublicKey = TOKEN_PROGRAM_ID) {
+    const bnNonce = new BN(nonce);
+    const guardian = this.program.provider.publicKey!;
+    const [vaultPda] = findVaultPda(guardian, bnNonce, this.program.programId);
+
+    const vaultAta = getAssociatedTokenAddressSync(mint, vaultPda, true, tokenProgram);
+    const guardianAta = getAssociatedTokenAddressSync(mint, guardian, false, tokenProgram);
+
+    const createGuardianAtaIx = createAssociatedTokenAccountIdempotentInstruction(
+      guardian,
+      guardianAta,
+      guardian,
+      mint,
+      tokenProgram,
+    );
+
+    const tx = await this.program.methods
+      .withdrawSpl!(bnNonce, new BN(amountTokens))
+      .accounts({
+        guardian,
+        vault: vaultPda,
+        vaultTokenAccount: vaultAta,
+        destinationTokenAccount: guardianAta,
+        mint,
+        tokenProgram,
+      })
+      .preInstructions([createGuardianAtaIx])
+      .rpc();
+
+    return tx;
+  }
+
+  // --- VAULT TOKEN ACCOUNTS ---
+
+  async createVaultTokenAccount(nonce: number, mint: PublicKey, tokenProgram: PublicKey = TOKEN_PROGRAM_ID) {
+    const bnNonce = new BN(nonce);
+    const guardian = this.program.provider.publicKey!;
+    const [vaultPda] = findVaultPda(guardian, bnNonce, this.program.programId);
+
+    const tx = await this.program.methods
+      .createVaultTokenAccount!(bnNonce)
+      .accounts({
+        guardian,
+        vault: vaultPda,
+        mint,
+        vaultTokenAccount: getAssociatedTokenAddressSync(mint, vaultPda, true, tokenProgram),
+        tokenProgram,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  async closeVaultTokenAccount(nonce: number, mint: PublicKey, tokenProgram: PublicKey = TOKEN_PROGRAM_ID) {
+    const bnNonce = new BN(nonce);
+    const guardian = this.program.provider.publicKey!;
+    const [vaultPda] = findVaultPda(guardian, bnNonce, this.program.programId);
+
+    const tx = await this.program.methods
+      .closeVaultTokenAccount!(bnNonce)
+      .accounts({
+        guardian,
+        vault: vaultPda,
+        mint,
+        vaultTokenAccount: getAssociatedTokenAddressSync(mint, vaultPda, true, tokenProgram),
+        tokenProgram,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  // --- SCHEDULED PAYMENTS ---
+
+  /** Recurring SOL payment, or SPL when `mint` is given. Times are unix seconds, 0 = none. */
+  async createSchedule(
+    vaultNonce: number,
+    scheduleNonce: number,
+    recipient: PublicKey,
+    amount: BN,
+    intervalSeconds: number,
+    mint: PublicKey | null = null,
+    startTime = 0,
+    endTime = 0,
+    maxPayments = 0
+  ) {
+    const bnVaultNonce = new BN(vaultNonce);
+    const bnScheduleNonce = new BN(scheduleNonce);
+    const guardian = this.program.provider.publicKey!;
+    const [vaultPda] = findVaultPda(guardian, bnVaultNonce, this.program.programId);
+    const [schedulePda] = findSchedulePda(vaultPda, bnScheduleNonce, this.program.programId);
+
+    const tx = await this.program.methods
+      .createSchedule!(
+        bnVaultNonce,
+        bnScheduleNonce,
+        recipient,
+        mint,
+        amount,
+        new BN(intervalSeconds),
+        new BN(startTime),
+        new BN(endTime),
+        maxPayments
+      )
+      .accounts({
+        guardian,
+        vault: vaultPda,
+        schedule: schedulePda,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  async cancelSchedule(vaultNonce: number, scheduleNonce: number) {
+    const bnVaultNonce = new BN(vaultNonce);
+    const bnScheduleNonce = new BN(scheduleNonce);
+    const guardian = this.program.provider.publicKey!;
+    const [vaultPda] = findVaultPda(guardian, bnVaultNonce, this.program.programId);
+    const [schedulePda] = findSchedulePda(vaultPda, bnScheduleNonce, this.program.programId);
+
+    const tx = await this.program.methods
+      .cancelSchedule!(bnVaultNonce, bnScheduleNonce)
+      .accounts({
+        guardian,
+        vault: vaultPda,
+        schedule: schedulePda,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  /** Pays the schedule's next due payment, callable by any wallet */
+  async crankSchedule(
+    guardian: PublicKey,
+    vaultNonce: number,
+    scheduleNonce: number,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID,
+    // Pyth price update for SPL schedules whose mint the vault prices with Pyth
+    feedAccount: PublicKey | null = null
+  ) {
+    const bnVaultNonce = new BN(vaultNonce);
+    const bnScheduleNonce = new BN(scheduleNonce);
+    const [vaultPda] = findVaultPda(guardian, bnVaultNonce, this.program.programId);
+    const [schedulePda] = findSchedulePda(vaultPda, bnScheduleNonce, this.program.programId);
+
+    const schedule = await (this.program.account as any).schedule.fetch(schedulePda);
+    const isSol = schedule.mint.equals(PublicKey.default);
+
+    const tx = await this.program.methods
+      .crankSchedule!(bnVaultNonce, bnScheduleNonce)
+      .accounts({
+        cranker: this.program.provider.publicKey!,
+        guardian,
+        vault: vaultPda,
+        schedule: schedulePda,
+        destination: isSol
+          ? schedule.recipient
+          : getAssociatedTokenAddressSync(schedule.mint, schedule.recipient, true, tokenProgram),
+        mint: isSol ? null : schedule.mint,
+        vaultTokenAccount: isSol
+          ? null
+          : getAssociatedTokenAddressSync(schedule.mint, vaultPda, true, tokenProgram),
+        tokenProgram: isSol ? null : tokenProgram,
+        priceUpdate: isSol ? null : feedAccount,
+        spendLedger: await this.spendLedgerOf(vaultPda),
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  // --- STREAMS ---
+
+  /** Linear SOL stream, the full amount is committed in the vault up front. Times are unix seconds. */
+  async createStream(
+    vaultNonce: number,
+    streamNonce: number,
+    recipient: PublicKey,
+    ratePerSecond: BN,
+    startTime: number,
+    endTime: number
+  ) {
+    const bnVaultNonce = new BN(vaultNonce);
+    const bnStreamNonce = new BN(streamNonce);
+    const guardian = this.program.provider.publicKey!;
+    const [vaultPda] = findVaultPda(guardian, bnVaultNonce, this.program.programId);
+    const [streamPda] = findStreamPda(vaultPda, bnStreamNonce, this.program.programId);
+
+    const tx = await this.program.methods
+      .createStream!(bnVaultNonce, bnStreamNonce, recipient, ratePerSecond, new BN(startTime), new BN(endTime))
+      .accounts({
+        guardian,
+        vault: vaultPda,
+        stream: streamPda,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  /** Withdraws everything vested so far, signed by the stream's recipient */
+  async withdrawStream(guardian: PublicKey, vaultNonce: number, streamNonce: number) {
+    const bnVaultNonce = new BN(vaultNonce);
+    const bnStreamNonce = new BN(streamNonce);
+    const [vaultPda] = findVaultPda(guardian, bnVaultNonce, this.program.programId);
+    const [streamPda] = findStreamPda(vaultPda, bnStreamNonce, this.program.programId);
+
+    const tx = await this.program.methods
+      .withdrawStream!(bnVaultNonce, bnStreamNonce)
+      .accounts({
+        recipient: this.program.provider.publicKey!,
+        guardian,
+        vault: vaultPda,
+        stream: streamPda,
+        spendLedger: await this.spendLedgerOf(vaultPda),
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  async cancelStream(vaultNonce: number, streamNonce: number) {
+    const bnVaultNonce = new BN(vaultNonce);
+    const bnStreamNonce = new BN(streamNonce);
+    const guardian = this.program.provider.publicKey!;
+    const [vaultPda] = findVaultPda(guardian, bnVaultNonce, this.program.programId);
+    const [streamPda] = findStreamPda(vaultPda, bnStreamNonce, this.program.programId);
+
+    const stream = await (this.program.account as any).stream.fetch(streamPda);
+
+    const tx = await this.program.methods
+      .cancelStream!(bnVaultNonce, bnStreamNonce)
+      .accounts({
+        guardian,
+        vault: vaultPda,
+        stream: streamPda,
+        recipient: stream.recipient,
+        spendLedger: await this.spendLedgerOf(vaultPda),
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  // --- DEPOSITS ---
+
+  async depositSol(guardian: PublicKey, vaultNonce: number, amountSol: number, trackContribution = true) {
+    const bnNonce = new BN(vaultNonce);
+    const depositor = this.program.provider.publicKey!;
+    const [vaultPda] = findVaultPda(guardian, bnNonce, this.program.programId);
+    const [contributionPda] = findContributionPda(vaultPda, depositor, PublicKey.default, this.program.programId);
+
+    const tx = await this.program.methods
+      .depositSol!(bnNonce, new BN(amountSol * 1_000_000_000))
+      .accounts({
+        depositor,
+        guardian,
+        vault: vaultPda,
+        contribution: trackContribution ? contributionPda : null,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  async depositSpl(
+    guardian: PublicKey,
+    vaultNonce: number,
+    mint: PublicKey,
+    amountTokens: number,
+    trackContribution = true,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID,
+    // Extra account metas for Token-2022 transfer hook mints
+    transferHookAccounts: AccountMeta[] = []
+  ) {
+    const bnNonce = new BN(vaultNonce);
+    const depositor = this.program.provider.publicKey!;
+    const [vaultPda] = findVaultPda(guardian, bnNonce, this.program.programId);
+    const [contributionPda] = findContributionPda(vaultPda, depositor, mint, this.program.programId);
+
+    const tx = await this.program.methods
+      .depositSpl!(bnNonce, new BN(amountTokens))
+      .accounts({
+        depositor,
+        guardian,
+        vault: vaultPda,
+        mint,
+        depositorTokenAccount: getAssociatedTokenAddressSync(mint, depositor, false, tokenProgram),
+        vaultTokenAccount: getAssociatedTokenAddressSync(mint, vaultPda, true, tokenProgram),
+        contribution: trackContribution ? contributionPda : null,
+        tokenProgram,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(transferHookAccounts)
+      .rpc();
+
+    return tx;
+  }
 }
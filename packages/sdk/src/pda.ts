@@ -3,6 +3,7 @@ import { BN } from "@coral-xyz/anchor";
 
 export const FUIN_PROGRAM_ID = new PublicKey("E6GkTAh6m3DacsKuUKQ64gn85mZof4D96dTNPLQAoSiy");
 export const METEORA_DLMM_PROGRAM = new PublicKey("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
+export const MEMO_PROGRAM_ID = new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 
 // Permission constants (bitmask)
 export const CAN_SWAP = 1;
@@ -31,3 +32,38 @@ export const findDelegatePda = (vault: PublicKey, nonce: BN, programId = FUIN_PR
     programId
   );
 };
+
+// mint is PublicKey.default for SOL deposits
+export const findContributionPda = (vault: PublicKey, depositor: PublicKey, mint: PublicKey, programId = FUIN_PROGRAM_ID) => {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("contribution"),
+      vault.toBuffer(),
+      depositor.toBuffer(),
+      mint.toBuffer(),
+    ],
+    programId
+  );
+};
+
+export const findSchedulePda = (vault: PublicKey, nonce: BN, programId = FUIN_PROGRAM_ID) => {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("schedule"),
+      vault.toBuffer(),
+      nonce.toArrayLike(Buffer, "le", 8),
+    ],
+    programId
+  );
+};
+
+export const findStreamPda = (vault: PublicKey, nonce: BN, programId = FUIN_PROGRAM_ID) => {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("stream"),
+      vault.toBuffer(),
+      nonce.toArrayLike(Buffer, "le", 8),
+    ],
+    programId
+  );
+};
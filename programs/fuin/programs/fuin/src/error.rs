@@ -52,4 +52,106 @@ pub enum ErrorCode{
     DelegateInactive,
     #[msg("Swap execution failed")]
     SwapFailed,
+    #[msg("Delegate is still active and unexpired")]
+    DelegateStillActive,
+    #[msg("Delegate has been revoked")]
+    DelegateRevoked,
+    #[msg("Key rotation is not enabled for this delegate")]
+    KeyRotationDisabled,
+    #[msg("Account is already in the current layout")]
+    AlreadyMigrated,
+    #[msg("Sub-delegate scope exceeds its parent")]
+    InvalidSubdelegateScope,
+    #[msg("Sub-delegates cannot issue further delegates")]
+    SubdelegationTooDeep,
+    #[msg("Parent delegate account is missing or does not match")]
+    InvalidParentDelegate,
+    #[msg("Intent does not match the supplied accounts")]
+    InvalidIntent,
+    #[msg("Intent deadline has passed")]
+    IntentExpired,
+    #[msg("Route is not supported for intent execution")]
+    UnsupportedRoute,
+    #[msg("Missing Ed25519 signature verification instruction")]
+    MissingSignatureVerification,
+    #[msg("Ed25519 verification does not cover this intent")]
+    InvalidSignatureVerification,
+    #[msg("Gas tank balance is too low to reimburse the relayer")]
+    InsufficientGasTank,
+    #[msg("Relayer is not allowed by policy")]
+    RelayerNotAllowed,
+    #[msg("Vault must be migrated with migrate_vault first")]
+    VaultNotMigrated,
+    #[msg("Intent nonce is below the accepted window")]
+    IntentNonceStale,
+    #[msg("Intent nonce has already been used")]
+    IntentNonceReused,
+    #[msg("Spend ledger does not belong to this vault")]
+    InvalidSpendLedger,
+    #[msg("Oracle price is older than the allowed maximum age")]
+    StalePrice,
+    #[msg("Oracle price confidence interval is too wide")]
+    LowConfidencePrice,
+    #[msg("Oracle price update is not fully verified")]
+    UnverifiedPrice,
+    #[msg("Oracle account required by the mint's oracle policy is missing")]
+    MissingOracleAccount,
+    #[msg("Oracle account does not match the configured feed")]
+    InvalidOracleAccount,
+    #[msg("Primary and fallback oracle prices diverge too much")]
+    PriceDivergence,
+    #[msg("Invalid mint oracle configuration")]
+    InvalidOracleConfig,
+    #[msg("Pegged mint's oracle price is too far from its peg")]
+    MintDepegged,
+    #[msg("Feed id is required for a mint without an oracle policy")]
+    MissingFeedId,
+    #[msg("Vault balance above its rent-exempt minimum is too low")]
+    InsufficientFunds,
+    #[msg("Destination cannot receive lamports from the vault")]
+    InvalidDestination,
+    #[msg("Batch is empty, too large or its accounts don't match")]
+    InvalidBatch,
+    #[msg("Token account is not owned by the given token program")]
+    InvalidTokenProgram,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Vault token account registry is full")]
+    TokenAccountRegistryFull,
+    #[msg("Token account is not the vault's associated token account for the mint")]
+    NonCanonicalTokenAccount,
+    #[msg("Token account still holds tokens")]
+    TokenAccountNotEmpty,
+    #[msg("Mint carries a Token-2022 extension blocked by the vault")]
+    MintExtensionBlocked,
+    #[msg("Unknown Token-2022 extension type")]
+    InvalidMintPolicy,
+    #[msg("Memo is empty or too long")]
+    InvalidMemo,
+    #[msg("Memo program account is required to attach a memo")]
+    MissingMemoProgram,
+    #[msg("Mint is not allowed by the vault's mint policy")]
+    MintNotAllowed,
+    #[msg("Transfers above the vault's threshold must carry a memo")]
+    MemoRequired,
+    #[msg("Invalid schedule parameters")]
+    InvalidSchedule,
+    #[msg("Scheduled payment is not due yet")]
+    ScheduleNotDue,
+    #[msg("Schedule has made all of its payments")]
+    ScheduleComplete,
+    #[msg("Mint, vault token account and token program are required for SPL payments")]
+    MissingTokenAccounts,
+    #[msg("Invalid stream parameters")]
+    InvalidStream,
+    #[msg("Nothing has vested since the last withdrawal")]
+    NothingToWithdraw,
+    #[msg("Vault has a spend ledger registered, it must be passed to record the execution")]
+    MissingSpendLedger,
+    #[msg("Spend exceeds the vault's per-transaction USD cap")]
+    UsdPerTxLimitExceeded,
+    #[msg("Spend exceeds the vault's daily USD cap")]
+    UsdDailyLimitExceeded,
+    #[msg("Payout would leave the recipient below rent exemption, fund the recipient first")]
+    RecipientNotRentExempt,
 }
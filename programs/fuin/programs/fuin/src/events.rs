@@ -0,0 +1,310 @@
+use anchor_lang::prelude::*;
+use crate::state::{OracleKind, PriceSource};
+
+#[event]
+pub struct VaultInitialized {
+    pub vault: Pubkey,
+    pub guardian: Pubkey,
+    pub nonce: u64,
+    pub daily_cap: u64,
+    pub per_tx_cap: u64,
+}
+
+#[event]
+pub struct VaultFrozen {
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct VaultUnfrozen {
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct PolicyUpdated {
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub vault: Pubkey,
+    pub guardian: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct Deposited {
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+    // Pubkey::default() for SOL
+    pub mint: Pubkey,
+    // credited to the vault, net of Token-2022 transfer fees
+    pub amount: u64,
+    pub transfer_fee: u64,
+    // depositor's running total for this mint, 0 when not tracked
+    pub total_deposited: u64,
+}
+
+#[event]
+pub struct SplWithdrawn {
+    pub vault: Pubkey,
+    pub guardian: Pubkey,
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VaultTokenAccountCreated {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub token_program: Pubkey,
+}
+
+#[event]
+pub struct VaultTokenAccountClosed {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+}
+
+#[event]
+pub struct DelegateIssued {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub authority: Pubkey,
+    pub parent: Option<Pubkey>,
+    pub permissions: u16,
+    pub daily_limit: u64,
+    pub max_uses: u32,
+    pub expiry: i64,
+}
+
+#[event]
+pub struct DelegateUpdated {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub permissions: u16,
+    pub daily_limit: u64,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub expiry: i64,
+    pub allow_key_rotation: bool,
+}
+
+#[event]
+pub struct DelegateRevoked {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+}
+
+// Pause and resume, revoke has its own event
+#[event]
+pub struct DelegateStatusChanged {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub is_active: bool,
+}
+
+#[event]
+pub struct DelegateKeyRotated {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct DelegateClosed {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct GasTankUpdated {
+    pub vault: Pubkey,
+    pub fee_per_call: u64,
+    pub daily_cap: u64,
+    pub balance: u64,
+}
+
+#[event]
+pub struct GasTankClosed {
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct SpendLedgerInitialized {
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct SpendLedgerClosed {
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct TransferExecuted {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub relayer: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    // payment reference, also CPI'd to the Memo program
+    pub memo: Option<String>,
+    // lamports paid to the relayer from the gas tank
+    pub relayer_fee: u64,
+    pub vault_daily_spent: u64,
+    pub delegate_daily_spent: u64,
+    pub delegate_uses: u32,
+}
+
+#[event]
+pub struct SplTransferExecuted {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub relayer: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    // gross, charged against limits
+    pub amount: u64,
+    // received by the destination after Token-2022 transfer fees
+    pub net_amount: u64,
+    pub transfer_fee: u64,
+    // payment reference, also CPI'd to the Memo program
+    pub memo: Option<String>,
+    // micro-USD
+    pub usd_value: u64,
+    pub price_source: PriceSource,
+    pub oracle: OracleKind,
+    pub relayer_fee: u64,
+    pub vault_daily_spent: u64,
+    // micro-USD
+    pub vault_daily_usd_spent: u64,
+    pub delegate_daily_spent: u64,
+    pub delegate_uses: u32,
+}
+
+#[event]
+pub struct SwapExecuted {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub relayer: Pubkey,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+    // micro-USD
+    pub usd_value: u64,
+    pub price_source: PriceSource,
+    pub oracle: OracleKind,
+    pub relayer_fee: u64,
+    pub vault_daily_spent: u64,
+    // micro-USD
+    pub vault_daily_usd_spent: u64,
+    pub delegate_daily_spent: u64,
+    pub delegate_uses: u32,
+}
+
+#[event]
+pub struct VaultMigrated {
+    pub vault: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    // rent the guardian paid for the larger account
+    pub rent_top_up: u64,
+}
+
+#[event]
+pub struct DelegateMigrated {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub rent_top_up: u64,
+}
+
+#[event]
+pub struct BatchExecuted {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub relayer: Pubkey,
+    pub items: u8,
+    // sum of item amounts, lamports and token units alike, as charged to limits
+    pub total_amount: u64,
+    // uses charged to the delegate
+    pub uses: u32,
+    pub relayer_fee: u64,
+    pub vault_daily_spent: u64,
+    // micro-USD
+    pub vault_daily_usd_spent: u64,
+    pub delegate_daily_spent: u64,
+    pub delegate_uses: u32,
+}
+
+#[event]
+pub struct ScheduleCreated {
+    pub vault: Pubkey,
+    pub schedule: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub interval_seconds: i64,
+    pub start_time: i64,
+}
+
+#[event]
+pub struct ScheduleCancelled {
+    pub vault: Pubkey,
+    pub schedule: Pubkey,
+    pub payments_made: u32,
+}
+
+#[event]
+pub struct SchedulePaymentExecuted {
+    pub vault: Pubkey,
+    pub schedule: Pubkey,
+    pub cranker: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    // micro-USD, 0 for SOL and unpriced mints
+    pub usd_value: u64,
+    pub payments_made: u32,
+    pub next_payment_at: i64,
+    pub vault_daily_spent: u64,
+    // micro-USD
+    pub vault_daily_usd_spent: u64,
+}
+
+#[event]
+pub struct StreamCreated {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub rate_per_second: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub total: u64,
+}
+
+#[event]
+pub struct StreamWithdrawn {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub withdrawn: u64,
+}
+
+#[event]
+pub struct StreamCancelled {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    // vested amount paid to the recipient on cancel
+    pub paid_out: u64,
+    // unvested amount released back to the vault
+    pub returned: u64,
+    // vested amount kept committed for the recipient to withdraw, the stream stays open until it's claimed
+    pub claimable: u64,
+}
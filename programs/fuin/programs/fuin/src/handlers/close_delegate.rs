@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, events::DelegateClosed, state::{Delegate, Vault}};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_delegate: u64)]
+pub struct CloseDelegate<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"delegate",
+            vault.key().as_ref(),
+            &nonce_delegate.to_le_bytes(),
+        ],
+        bump = delegate.bump,
+        has_one = vault,
+        close = guardian,
+    )]
+    pub delegate: Account<'info, Delegate>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_delegate: u64)]
+pub struct CrankCloseDelegate<'info>{
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Validated via vault PDA seeds, receives the delegate rent
+    #[account(mut)]
+    pub guardian: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"vault", guardian.key.as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"delegate",
+            vault.key().as_ref(),
+            &nonce_delegate.to_le_bytes(),
+        ],
+        bump = delegate.bump,
+        has_one = vault,
+        close = guardian,
+    )]
+    pub delegate: Account<'info, Delegate>,
+}
+
+pub fn close_delegate(ctx: Context<CloseDelegate>, _nonce_vault: u64, _nonce_delegate: u64)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    // Guardian may close anything it has already revoked/paused, or that has run out
+    let delegate = &ctx.accounts.delegate;
+    require!(
+        !delegate.is_active || clock.unix_timestamp > delegate.expiry,
+        ErrorCode::DelegateStillActive
+    );
+
+    emit_cpi!(DelegateClosed {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+    });
+
+    msg!("Delegate closed, rent returned to guardian");
+    Ok(())
+}
+
+pub fn crank_close_delegate(ctx: Context<CrankCloseDelegate>, _nonce_vault: u64, _nonce_delegate: u64)->Result<()>{
+    let clock = Clock::get()?;
+
+    // Anyone can crank, but only once the delegate is past expiry (revoke sets expiry to 0)
+    require!(
+        clock.unix_timestamp > ctx.accounts.delegate.expiry,
+        ErrorCode::DelegateStillActive
+    );
+
+    emit_cpi!(DelegateClosed {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+    });
+
+    msg!("Expired delegate closed by crank, rent returned to guardian");
+    Ok(())
+}
@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::{state::{Delegate, Vault}, error::ErrorCode};
+use crate::{state::{Delegate, Vault}, error::ErrorCode, events::{DelegateRevoked, DelegateStatusChanged}};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(nonce_vault: u64, nonce_delegate: u64)]
 pub struct DelegateControl<'info>{
@@ -62,5 +63,17 @@ pub fn delegate_control(
         }
     }
 
+    let vault = ctx.accounts.vault.key();
+    let delegate = ctx.accounts.delegate.key();
+    if status == 0 {
+        emit_cpi!(DelegateRevoked { vault, delegate });
+    } else {
+        emit_cpi!(DelegateStatusChanged {
+            vault,
+            delegate,
+            is_active: ctx.accounts.delegate.is_active,
+        });
+    }
+
     Ok(())
 }
@@ -0,0 +1,208 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::spl_token_2022,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{error::ErrorCode, events::Deposited, state::{Contribution, Vault, VaultTokenAccount}, token_extensions::transfer_fee};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct DepositSol<'info>{
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    /// CHECK: Validated via vault PDA seeds (vault is derived from guardian's key)
+    pub guardian: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key.as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Per-depositor running total, created on first use, optional
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"contribution", vault.key().as_ref(), depositor.key().as_ref(), Pubkey::default().as_ref()],
+        bump,
+        space = Contribution::DISCRIMINATOR.len() + Contribution::INIT_SPACE,
+    )]
+    pub contribution: Option<Account<'info, Contribution>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Remaining accounts are forwarded as the mint's transfer hook extra accounts
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct DepositSpl<'info>{
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    /// CHECK: Validated via vault PDA seeds (vault is derived from guardian's key)
+    pub guardian: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key.as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = depositor_token_account.mint == mint.key() @ErrorCode::MintMismatch,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Vault's associated token account, created on first deposit of the mint
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Per-depositor running total, created on first use, optional
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"contribution", vault.key().as_ref(), depositor.key().as_ref(), mint.key().as_ref()],
+        bump,
+        space = Contribution::DISCRIMINATOR.len() + Contribution::INIT_SPACE,
+    )]
+    pub contribution: Option<Account<'info, Contribution>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn deposit_sol(ctx: Context<DepositSol>, _nonce: u64, amount: u64)->Result<()>{
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.depositor.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let total_deposited = record_contribution(
+        ctx.accounts.contribution.as_mut(),
+        ctx.bumps.contribution.unwrap_or_default(),
+        ctx.accounts.vault.key(),
+        ctx.accounts.depositor.key(),
+        Pubkey::default(),
+        amount,
+    )?;
+
+    emit_cpi!(Deposited {
+        vault: ctx.accounts.vault.key(),
+        depositor: ctx.accounts.depositor.key(),
+        mint: Pubkey::default(),
+        amount,
+        transfer_fee: 0,
+        total_deposited,
+    });
+
+    msg!("Deposited {} lamports", amount);
+    Ok(())
+}
+
+// Contributions and the event carry what the vault actually received, net of Token-2022 transfer fees
+pub fn deposit_spl<'info>(ctx: Context<'_, '_, '_, 'info, DepositSpl<'info>>, _nonce: u64, amount: u64)->Result<()>{
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let fee = transfer_fee(&mint_info, Clock::get()?.epoch, amount)?;
+    let net_amount = amount.checked_sub(fee).ok_or(ErrorCode::Overflow)?;
+
+    // Signed by the depositor, so no seeds. Resolves transfer hook accounts like `transfer_spl_from_vault`.
+    spl_token_2022::onchain::invoke_transfer_checked(
+        ctx.accounts.token_program.key,
+        ctx.accounts.depositor_token_account.to_account_info(),
+        mint_info,
+        ctx.accounts.vault_token_account.to_account_info(),
+        ctx.accounts.depositor.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        ctx.accounts.mint.decimals,
+        &[],
+    )?;
+
+    // A full registry doesn't block deposits
+    let vault_token_account = VaultTokenAccount {
+        mint: ctx.accounts.mint.key(),
+        address: ctx.accounts.vault_token_account.key(),
+        token_program: ctx.accounts.token_program.key(),
+    };
+    ctx.accounts.vault.register_token_account(vault_token_account);
+
+    let total_deposited = record_contribution(
+        ctx.accounts.contribution.as_mut(),
+        ctx.bumps.contribution.unwrap_or_default(),
+        ctx.accounts.vault.key(),
+        ctx.accounts.depositor.key(),
+        ctx.accounts.mint.key(),
+        net_amount,
+    )?;
+
+    emit_cpi!(Deposited {
+        vault: ctx.accounts.vault.key(),
+        depositor: ctx.accounts.depositor.key(),
+        mint: ctx.accounts.mint.key(),
+        amount: net_amount,
+        transfer_fee: fee,
+        total_deposited,
+    });
+
+    msg!("Deposited {} tokens, {} withheld as transfer fee", net_amount, fee);
+    Ok(())
+}
+
+// Adds the deposit to the depositor's running total, if tracked. Returns the new total (0 if untracked).
+fn record_contribution(
+    contribution: Option<&mut Account<Contribution>>,
+    bump: u8,
+    vault: Pubkey,
+    depositor: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+)->Result<u64>{
+    let Some(contribution) = contribution else {
+        return Ok(0);
+    };
+
+    // Freshly created by init_if_needed
+    if contribution.vault == Pubkey::default() {
+        contribution.vault = vault;
+        contribution.depositor = depositor;
+        contribution.mint = mint;
+        contribution.bump = bump;
+    }
+
+    contribution.total_deposited = contribution
+        .total_deposited
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+    contribution.deposits = contribution.deposits.saturating_add(1);
+    contribution.last_deposit_at = Clock::get()?.unix_timestamp;
+
+    Ok(contribution.total_deposited)
+}
@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{
+    error::ErrorCode,
+    events::BatchExecuted,
+    state::{
+        delegate::CAN_TRANSFER,
+        spend_ledger::{ROUTE_KIND_SPL_TRANSFER, ROUTE_KIND_TRANSFER},
+        Delegate, GasTank, OracleKind, PriceSource, SpendEntry, SpendLedger, Vault,
+    },
+    pricing::{calculate_usd_value, OracleAccounts, Rounding},
+    token_extensions::check_blocked_extensions,
+};
+use super::{
+    charge_usd_limits, record_spend, reimburse_relayer, transfer_lamports_from_vault, transfer_spl_from_vault,
+    validate_and_update_batch_limits, validate_memo_policy, validate_mint_policy,
+    validate_program_policy, validate_relayer_policy,
+};
+
+pub const MAX_BATCH_ITEMS: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum BatchTransfer {
+    // Remaining accounts: destination
+    Sol { amount: u64 },
+    // Remaining accounts: vault token account, mint, destination token account, Pyth price update,
+    // Switchboard feed. Oracle accounts a mint doesn't need are passed as the Fuin program ID.
+    Spl { amount: u64, feed_id: Option<String> },
+}
+
+impl BatchTransfer {
+    pub fn amount(&self) -> u64 {
+        match self {
+            BatchTransfer::Sol { amount } | BatchTransfer::Spl { amount, .. } => *amount,
+        }
+    }
+
+    fn account_count(&self) -> usize {
+        match self {
+            BatchTransfer::Sol { .. } => 1,
+            BatchTransfer::Spl { .. } => 5,
+        }
+    }
+}
+
+/// Remaining accounts hold each item's accounts in order, see `BatchTransfer`.
+/// Transfer hook mints and memos aren't supported in batches.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_delegate: u64)]
+pub struct ExecuteBatch<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    pub delegate_key: Signer<'info>,
+
+    /// CHECK: Validated via vault PDA seeds (vault is derived from guardian's key)
+    pub guardian: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"vault",
+            guardian.key.as_ref(),
+            &nonce_vault.to_le_bytes(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"delegate",
+            vault.key().as_ref(),
+            &nonce_delegate.to_le_bytes(),
+        ],
+        bump = delegate.bump,
+        constraint = delegate.vault == vault.key() @ErrorCode::InvalidSession,
+        constraint = delegate.authority == delegate_key.key() @ErrorCode::InvalidSession,
+    )]
+    pub delegate: Account<'info, Delegate>,
+
+    /// Parent of a sub-delegate, required when `delegate.parent` is set
+    #[account(mut)]
+    pub parent_delegate: Option<Account<'info, Delegate>>,
+
+    /// Guardian-funded tank that reimburses the relayer, optional
+    #[account(
+        mut,
+        seeds = [b"gas_tank", vault.key().as_ref()],
+        bump = gas_tank.bump,
+    )]
+    pub gas_tank: Option<Account<'info, GasTank>>,
+
+    /// Audit ring buffer of recent executions, required once registered on the vault
+    #[account(
+        mut,
+        address = vault.spend_ledger.unwrap_or_default() @ErrorCode::InvalidSpendLedger,
+    )]
+    pub spend_ledger: Option<AccountLoader<'info, SpendLedger>>,
+
+    /// Token program for every SPL item, required when the batch has any
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// All items succeed or the whole instruction fails. The relayer is reimbursed once.
+pub fn execute_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteBatch<'info>>,
+    _nonce_vault: u64,
+    _nonce_delegate: u64,
+    items: Vec<BatchTransfer>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(ctx.accounts.delegate.has_permission(CAN_TRANSFER), ErrorCode::PermissionDenied);
+    validate_relayer_policy(&ctx.accounts.vault, ctx.accounts.relayer.key)?;
+
+    require!(!items.is_empty() && items.len() <= MAX_BATCH_ITEMS, ErrorCode::InvalidBatch);
+    require!(
+        ctx.remaining_accounts.len() == items.iter().map(BatchTransfer::account_count).sum::<usize>(),
+        ErrorCode::InvalidBatch
+    );
+
+    // SOL items need no CPI, see execute_transfer. SPL items all go through one token program.
+    let token_program = if items.iter().any(|item| matches!(item, BatchTransfer::Spl { .. })) {
+        let token_program = ctx.accounts.token_program.as_ref().ok_or(ErrorCode::InvalidTokenProgram)?;
+        validate_program_policy(&ctx.accounts.vault, &token_program.key())?;
+        Some(token_program.to_account_info())
+    } else {
+        None
+    };
+
+    let amounts: Vec<u64> = items.iter().map(BatchTransfer::amount).collect();
+    for &amount in &amounts {
+        validate_memo_policy(&ctx.accounts.vault, amount, None)?;
+    }
+
+    let uses = match ctx.accounts.vault.policies.spending.uses_per_batch {
+        0 => items.len() as u32,
+        uses => uses,
+    };
+    validate_and_update_batch_limits(
+        &mut ctx.accounts.vault,
+        &mut ctx.accounts.delegate,
+        ctx.accounts.parent_delegate.as_mut(),
+        CAN_TRANSFER,
+        &clock,
+        &amounts,
+        uses,
+    )?;
+
+    let vault_key = ctx.accounts.vault.key();
+    let mut accounts = ctx.remaining_accounts;
+    for item in &items {
+        let (item_accounts, rest) = accounts.split_at(item.account_count());
+        accounts = rest;
+
+        let (amount, mint, destination, route_kind, usd_value, price_source, oracle_kind) = match item {
+            &BatchTransfer::Sol { amount } => {
+                let destination_info = &item_accounts[0];
+                transfer_lamports_from_vault(&ctx.accounts.vault, destination_info, amount)?;
+                (amount, Pubkey::default(), destination_info.key(), ROUTE_KIND_TRANSFER, 0, PriceSource::Spot, OracleKind::Unpriced)
+            },
+            BatchTransfer::Spl { amount, feed_id } => {
+                let amount = *amount;
+                let (source_info, mint_info, destination_info) = (&item_accounts[0], &item_accounts[1], &item_accounts[2]);
+                let token_program = token_program.as_ref().ok_or(ErrorCode::InvalidTokenProgram)?;
+
+                // Remaining accounts skip Anchor's constraints, so check what `ExecuteSplTransfer` would
+                let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+                let destination = InterfaceAccount::<TokenAccount>::try_from(destination_info)?;
+                require_keys_eq!(
+                    source_info.key(),
+                    get_associated_token_address_with_program_id(&vault_key, &mint.key(), token_program.key),
+                    ErrorCode::NonCanonicalTokenAccount
+                );
+                require_keys_eq!(destination.mint, mint.key(), ErrorCode::MintMismatch);
+                validate_mint_policy(&ctx.accounts.vault, &mint.key())?;
+                check_blocked_extensions(mint_info, &ctx.accounts.vault.policies.mints.blocked_extensions)?;
+
+                // Valued like `ExecuteSplTransfer` and charged against the USD caps item by item
+                let price_update = optional_account(&item_accounts[3])
+                    .map(Account::<PriceUpdateV2>::try_from)
+                    .transpose()?;
+                let oracles = OracleAccounts {
+                    price_update: price_update.as_deref(),
+                    switchboard_feed: optional_account(&item_accounts[4]),
+                };
+                let valuation = calculate_usd_value(
+                    &oracles,
+                    &mint.key(),
+                    feed_id.as_deref(),
+                    amount,
+                    mint.decimals,
+                    &ctx.accounts.vault.policies.oracle,
+                    Rounding::Up,
+                )?;
+                charge_usd_limits(&mut ctx.accounts.vault, &[valuation.usd_value])?;
+
+                transfer_spl_from_vault(
+                    &ctx.accounts.vault,
+                    token_program,
+                    source_info,
+                    mint_info,
+                    destination_info,
+                    &[],
+                    amount,
+                    mint.decimals,
+                )?;
+                (amount, mint.key(), destination.key(), ROUTE_KIND_SPL_TRANSFER, valuation.usd_value, valuation.source, valuation.oracle)
+            },
+        };
+
+        record_spend(
+            &ctx.accounts.vault,
+            ctx.accounts.spend_ledger.as_ref(),
+            SpendEntry {
+                slot: clock.slot,
+                amount,
+                usd_value,
+                delegate: ctx.accounts.delegate.key(),
+                mint,
+                destination,
+                route_kind,
+                price_source: price_source as u8,
+                oracle_kind: oracle_kind as u8,
+                _padding: [0; 5],
+            },
+        )?;
+    }
+
+    let relayer_fee = reimburse_relayer(
+        ctx.accounts.gas_tank.as_mut(),
+        &ctx.accounts.relayer.to_account_info(),
+        &clock,
+    )?;
+
+    emit_cpi!(BatchExecuted {
+        vault: vault_key,
+        delegate: ctx.accounts.delegate.key(),
+        relayer: ctx.accounts.relayer.key(),
+        items: items.len() as u8,
+        total_amount: amounts.iter().sum(),
+        uses,
+        relayer_fee,
+        vault_daily_spent: ctx.accounts.vault.policies.spending.daily_spent,
+        vault_daily_usd_spent: ctx.accounts.vault.policies.spending.daily_usd_spent,
+        delegate_daily_spent: ctx.accounts.delegate.daily_spent,
+        delegate_uses: ctx.accounts.delegate.uses,
+    });
+
+    msg!("Batch executed: {} transfers, relayer {}", items.len(), ctx.accounts.relayer.key());
+    Ok(())
+}
+
+// Anchor's convention for a skipped optional account
+fn optional_account<'a, 'info>(info: &'a AccountInfo<'info>) -> Option<&'a AccountInfo<'info>> {
+    (info.key() != crate::ID).then_some(info)
+}
@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use solana_sdk_ids::sysvar::instructions;
+
+use crate::{error::ErrorCode, events::TransferExecuted, intent::{Intent, verify_ed25519_signature}, state::{Delegate, GasTank, OracleKind, PriceSource, SpendEntry, SpendLedger, Route, Vault, spend_ledger::ROUTE_KIND_TRANSFER}};
+use super::{record_spend, reimburse_relayer, transfer_lamports_from_vault, validate_and_update_limits, validate_intent, validate_memo_policy, validate_relayer_policy};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault:u64, nonce_delegate:u64)]
+pub struct ExecuteIntent<'info>{
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// CHECK: Validated via vault PDA seeds (vault is derived from guardian's key)
+    pub guardian: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"vault",
+            guardian.key.as_ref(),
+            &nonce_vault.to_le_bytes(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    // No authority constraint here, the delegate proves itself through the Ed25519 instruction
+    #[account(
+        mut,
+        seeds = [
+            b"delegate",
+            vault.key().as_ref(),
+            &nonce_delegate.to_le_bytes(),
+        ],
+        bump = delegate.bump,
+        constraint = delegate.vault == vault.key() @ErrorCode::InvalidSession,
+    )]
+    pub delegate: Account<'info, Delegate>,
+
+    /// Parent of a sub-delegate, required when `delegate.parent` is set
+    #[account(mut)]
+    pub parent_delegate: Option<Account<'info, Delegate>>,
+
+    /// Guardian-funded tank that reimburses the relayer, optional
+    #[account(
+        mut,
+        seeds = [b"gas_tank", vault.key().as_ref()],
+        bump = gas_tank.bump,
+    )]
+    pub gas_tank: Option<Account<'info, GasTank>>,
+
+    /// Audit ring buffer of recent executions, required once registered on the vault
+    #[account(
+        mut,
+        address = vault.spend_ledger.unwrap_or_default() @ErrorCode::InvalidSpendLedger,
+    )]
+    pub spend_ledger: Option<AccountLoader<'info, SpendLedger>>,
+
+    /// CHECK: Destination address, must match the signed route
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+
+    /// CHECK: Instructions sysvar, used to find the Ed25519 verification
+    #[account(address = instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Relays a delegate-signed intent. Only `Route::Transfer` (SOL) is supported, `SimpleSwap` and
+/// `StakeSOL` intents are rejected with `UnsupportedRoute` until their execution paths exist.
+pub fn execute_intent(ctx: Context<ExecuteIntent>, _nonce_vault: u64, _nonce_delegate: u64, intent: Intent)->Result<()>{
+    let clock = Clock::get()?;
+
+    // Intent must be bound to these exact accounts
+    require_keys_eq!(intent.vault, ctx.accounts.vault.key(), ErrorCode::InvalidIntent);
+    require_keys_eq!(intent.delegate, ctx.accounts.delegate.key(), ErrorCode::InvalidIntent);
+
+    verify_ed25519_signature(
+        &ctx.accounts.instructions_sysvar,
+        &ctx.accounts.delegate.authority,
+        &intent.message()?,
+    )?;
+
+    // Deadline and nonce replay checks
+    validate_intent(&mut ctx.accounts.delegate, &clock, intent.nonce, intent.deadline)?;
+
+    // Permission check
+    let permission = intent.route.required_permission();
+    require!(ctx.accounts.delegate.has_permission(permission), ErrorCode::PermissionDenied);
+
+    // Relayer policy check
+    validate_relayer_policy(&ctx.accounts.vault, ctx.accounts.relayer.key)?;
+
+    // Only plain SOL transfers can be relayed as intents for now, see the doc comment
+    let Route::Transfer { amount, destination } = intent.route else {
+        return err!(ErrorCode::UnsupportedRoute);
+    };
+    require_keys_eq!(destination, ctx.accounts.destination.key(), ErrorCode::InvalidIntent);
+    validate_memo_policy(&ctx.accounts.vault, amount, None)?;
+
+    validate_and_update_limits(
+        &mut ctx.accounts.vault,
+        &mut ctx.accounts.delegate,
+        ctx.accounts.parent_delegate.as_mut(),
+        permission,
+        &clock,
+        amount
+    )?;
+
+    transfer_lamports_from_vault(
+        &ctx.accounts.vault,
+        &ctx.accounts.destination,
+        amount,
+    )?;
+
+    record_spend(
+        &ctx.accounts.vault,
+        ctx.accounts.spend_ledger.as_ref(),
+        SpendEntry {
+            slot: clock.slot,
+            amount,
+            usd_value: 0,
+            delegate: ctx.accounts.delegate.key(),
+            mint: Pubkey::default(),
+            destination,
+            route_kind: ROUTE_KIND_TRANSFER,
+            price_source: PriceSource::Spot as u8,
+            oracle_kind: OracleKind::Unpriced as u8,
+            _padding: [0; 5],
+        },
+    )?;
+
+    let relayer_fee = reimburse_relayer(
+        ctx.accounts.gas_tank.as_mut(),
+        &ctx.accounts.relayer.to_account_info(),
+        &clock,
+    )?;
+
+    emit_cpi!(TransferExecuted {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+        relayer: ctx.accounts.relayer.key(),
+        destination,
+        amount,
+        memo: None,
+        relayer_fee,
+        vault_daily_spent: ctx.accounts.vault.policies.spending.daily_spent,
+        delegate_daily_spent: ctx.accounts.delegate.daily_spent,
+        delegate_uses: ctx.accounts.delegate.uses,
+    });
+
+    msg!("Intent transfer executed: {} lamports, relayer {}", amount, ctx.accounts.relayer.key());
+    Ok(())
+}
@@ -1,9 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked};
+use anchor_spl::{associated_token::get_associated_token_address_with_program_id, memo::Memo, token_interface::{Mint, TokenAccount, TokenInterface}};
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
-use crate::{error::ErrorCode, state::{Delegate, Vault, delegate::CAN_TRANSFER}, pricing::calculate_usd_value};
-use super::{validate_and_update_limits, validate_program_policy};
+use crate::{error::ErrorCode, events::SplTransferExecuted, state::{Delegate, GasTank, SpendEntry, SpendLedger, Vault, delegate::CAN_TRANSFER, spend_ledger::ROUTE_KIND_SPL_TRANSFER}, pricing::{calculate_usd_value, OracleAccounts, Rounding}, token_extensions::{check_blocked_extensions, transfer_fee}};
+use super::{attach_memo, charge_usd_limits, record_spend, reimburse_relayer, transfer_spl_from_vault, validate_and_update_limits, validate_memo_policy, validate_mint_policy, validate_program_policy, validate_relayer_policy};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(nonce_vault:u64, nonce_delegate:u64)]
 pub struct ExecuteSplTransfer<'info>{
@@ -39,8 +40,29 @@ pub struct ExecuteSplTransfer<'info>{
     )]
     pub delegate: Account<'info, Delegate>,
 
+    /// Parent of a sub-delegate, required when `delegate.parent` is set
+    #[account(mut)]
+    pub parent_delegate: Option<Account<'info, Delegate>>,
+
+    /// Guardian-funded tank that reimburses the relayer, optional
     #[account(
         mut,
+        seeds = [b"gas_tank", vault.key().as_ref()],
+        bump = gas_tank.bump,
+    )]
+    pub gas_tank: Option<Account<'info, GasTank>>,
+
+    /// Audit ring buffer of recent executions, required once registered on the vault
+    #[account(
+        mut,
+        address = vault.spend_ledger.unwrap_or_default() @ErrorCode::InvalidSpendLedger,
+    )]
+    pub spend_ledger: Option<AccountLoader<'info, SpendLedger>>,
+
+    /// Vault's canonical associated token account for the mint
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&vault.key(), &mint.key(), &token_program.key()) @ErrorCode::NonCanonicalTokenAccount,
         constraint = vault_token_account.mint == mint.key() @ErrorCode::MintMismatch,
         constraint = vault_token_account.owner == vault.key() @ErrorCode::VaultOwnerMismatch,
     )]
@@ -56,56 +78,129 @@ pub struct ExecuteSplTransfer<'info>{
 
     pub token_program: Interface<'info, TokenInterface>,
 
-    pub price_update: Account<'info, PriceUpdateV2>,
+    /// Pyth price update, required when the mint is priced with Pyth, optional for pegged mints
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+
+    /// CHECK: Switchboard On-Demand pull feed, key and owner are checked against the oracle policy
+    pub switchboard_feed: Option<UncheckedAccount<'info>>,
+
+    /// SPL Memo program, required when a memo is attached
+    pub memo_program: Option<Program<'info, Memo>>,
 }
 
-pub fn execute_spl_transfer(ctx: Context<ExecuteSplTransfer>, _nonce_vault: u64, _nonce_delegate: u64, amount: u64, feed_id: String)->Result<()>{
+/// Remaining accounts are forwarded as the mint's transfer hook extra accounts
+pub fn execute_spl_transfer<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteSplTransfer<'info>>,
+    _nonce_vault: u64,
+    _nonce_delegate: u64,
+    amount: u64,
+    feed_id: Option<String>,
+    memo: Option<String>,
+)->Result<()>{
     let clock = Clock::get()?;
 
     // Permission check
     require!(ctx.accounts.delegate.has_permission(CAN_TRANSFER), ErrorCode::PermissionDenied);
 
+    // Relayer policy check
+    validate_relayer_policy(&ctx.accounts.vault, ctx.accounts.relayer.key)?;
+
     // Program policy check
     validate_program_policy(&ctx.accounts.vault, &ctx.accounts.token_program.key())?;
 
-    // USD value kept for future audit/logging use
-    let _usd_spend_amount = calculate_usd_value(
-        &ctx.accounts.price_update,
-        &feed_id,
+    // Mint policy and extension checks
+    validate_mint_policy(&ctx.accounts.vault, &ctx.accounts.mint.key())?;
+    let mint_info = ctx.accounts.mint.to_account_info();
+    check_blocked_extensions(&mint_info, &ctx.accounts.vault.policies.mints.blocked_extensions)?;
+
+    // Charged against the vault's USD caps, rounded up so a spend is never undervalued
+    let oracles = OracleAccounts {
+        price_update: ctx.accounts.price_update.as_deref(),
+        switchboard_feed: ctx.accounts.switchboard_feed.as_deref(),
+    };
+    let valuation = calculate_usd_value(
+        &oracles,
+        &ctx.accounts.mint.key(),
+        feed_id.as_deref(),
         amount,
-        ctx.accounts.mint.decimals
+        ctx.accounts.mint.decimals,
+        &ctx.accounts.vault.policies.oracle,
+        Rounding::Up,
     )?;
 
+    validate_memo_policy(&ctx.accounts.vault, amount, memo.as_deref())?;
+
+    // Limits are charged on the gross amount, transfer fees included
     validate_and_update_limits(
         &mut ctx.accounts.vault,
         &mut ctx.accounts.delegate,
+        ctx.accounts.parent_delegate.as_mut(),
+        CAN_TRANSFER,
         &clock,
         amount
     )?;
+    charge_usd_limits(&mut ctx.accounts.vault, &[valuation.usd_value])?;
 
-    let vault = &ctx.accounts.vault;
-    let seeds: &[&[&[u8]]] = &[&[
-        b"vault",
-        vault.guardian.as_ref(),
-        &vault.nonce.to_le_bytes(),
-        &[vault.bump],
-    ]];
-
-    let cpi_accounts = TransferChecked {
-        authority: vault.to_account_info(),
-        from: ctx.accounts.vault_token_account.to_account_info(),
-        mint: ctx.accounts.mint.to_account_info(),
-        to: ctx.accounts.destination_token_account.to_account_info(),
-    };
+    // What the destination actually receives
+    let fee = transfer_fee(&mint_info, clock.epoch, amount)?;
+    let net_amount = amount.checked_sub(fee).ok_or(ErrorCode::Overflow)?;
+
+    attach_memo(ctx.accounts.memo_program.as_ref(), memo.as_deref())?;
+
+    transfer_spl_from_vault(
+        &ctx.accounts.vault,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.vault_token_account.to_account_info(),
+        &mint_info,
+        &ctx.accounts.destination_token_account.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
 
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        cpi_accounts,
-        seeds
-    );
+    record_spend(
+        &ctx.accounts.vault,
+        ctx.accounts.spend_ledger.as_ref(),
+        SpendEntry {
+            slot: clock.slot,
+            amount,
+            usd_value: valuation.usd_value,
+            delegate: ctx.accounts.delegate.key(),
+            mint: ctx.accounts.mint.key(),
+            destination: ctx.accounts.destination_token_account.key(),
+            route_kind: ROUTE_KIND_SPL_TRANSFER,
+            price_source: valuation.source as u8,
+            oracle_kind: valuation.oracle as u8,
+            _padding: [0; 5],
+        },
+    )?;
 
-    transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+    let relayer_fee = reimburse_relayer(
+        ctx.accounts.gas_tank.as_mut(),
+        &ctx.accounts.relayer.to_account_info(),
+        &clock,
+    )?;
 
-    msg!("SPL Transfer executed: {} tokens", amount);
+    emit_cpi!(SplTransferExecuted {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+        relayer: ctx.accounts.relayer.key(),
+        mint: ctx.accounts.mint.key(),
+        destination: ctx.accounts.destination_token_account.key(),
+        amount,
+        net_amount,
+        transfer_fee: fee,
+        memo,
+        usd_value: valuation.usd_value,
+        price_source: valuation.source,
+        oracle: valuation.oracle,
+        relayer_fee,
+        vault_daily_spent: ctx.accounts.vault.policies.spending.daily_spent,
+        vault_daily_usd_spent: ctx.accounts.vault.policies.spending.daily_usd_spent,
+        delegate_daily_spent: ctx.accounts.delegate.daily_spent,
+        delegate_uses: ctx.accounts.delegate.uses,
+    });
+
+    msg!("SPL Transfer executed: {} tokens, relayer {}", amount, ctx.accounts.relayer.key());
     Ok(())
 }
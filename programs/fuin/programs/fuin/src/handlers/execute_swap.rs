@@ -1,15 +1,21 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
 use crate::{
     dlmm,
     error::ErrorCode,
-    pricing::calculate_usd_value,
-    state::{delegate::CAN_SWAP, Delegate, Vault},
+    events::SwapExecuted,
+    pricing::{calculate_usd_value, OracleAccounts, Rounding},
+    token_extensions::check_blocked_extensions,
+    state::{delegate::CAN_SWAP, spend_ledger::ROUTE_KIND_SWAP, Delegate, GasTank, SpendEntry, SpendLedger, Vault, VaultTokenAccount},
 };
-use super::{validate_and_update_limits, validate_program_policy};
+use super::{charge_usd_limits, record_spend, reimburse_relayer, validate_and_update_limits, validate_mint_policy, validate_program_policy, validate_relayer_policy};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(nonce_vault: u64, nonce_delegate: u64)]
 pub struct ExecuteSwap<'info> {
@@ -45,7 +51,30 @@ pub struct ExecuteSwap<'info> {
     )]
     pub delegate: Account<'info, Delegate>,
 
-    pub price_update: Account<'info, PriceUpdateV2>,
+    /// Parent of a sub-delegate, required when `delegate.parent` is set
+    #[account(mut)]
+    pub parent_delegate: Option<Box<Account<'info, Delegate>>>,
+
+    /// Guardian-funded tank that reimburses the relayer, optional
+    #[account(
+        mut,
+        seeds = [b"gas_tank", vault.key().as_ref()],
+        bump = gas_tank.bump,
+    )]
+    pub gas_tank: Option<Box<Account<'info, GasTank>>>,
+
+    /// Audit ring buffer of recent executions, required once registered on the vault
+    #[account(
+        mut,
+        address = vault.spend_ledger.unwrap_or_default() @ErrorCode::InvalidSpendLedger,
+    )]
+    pub spend_ledger: Option<AccountLoader<'info, SpendLedger>>,
+
+    /// Pyth price update, required when the input mint is priced with Pyth, optional for pegged mints
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+
+    /// CHECK: Switchboard On-Demand pull feed, key and owner are checked against the oracle policy
+    pub switchboard_feed: Option<UncheckedAccount<'info>>,
 
     // --- Meteora DLMM accounts ---
 
@@ -64,19 +93,28 @@ pub struct ExecuteSwap<'info> {
     #[account(mut)]
     pub reserve_y: UncheckedAccount<'info>,
 
-    /// Vault's token account for the input token
+    /// Vault's canonical associated token account for the input token
     #[account(
         mut,
+        address = get_associated_token_address_with_program_id(
+            &vault.key(),
+            &token_in_mint.key(),
+            token_in_mint.to_account_info().owner,
+        ) @ErrorCode::NonCanonicalTokenAccount,
+        constraint = user_token_in.mint == token_in_mint.key() @ErrorCode::MintMismatch,
         constraint = user_token_in.owner == vault.key() @ErrorCode::VaultOwnerMismatch,
     )]
-    pub user_token_in: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_in: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// Vault's token account for the output token
+    /// Vault's associated token account for the output token, created by the relayer if missing
     #[account(
-        mut,
-        constraint = user_token_out.owner == vault.key() @ErrorCode::VaultOwnerMismatch,
+        init_if_needed,
+        payer = relayer,
+        associated_token::mint = token_out_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_out_program,
     )]
-    pub user_token_out: InterfaceAccount<'info, TokenAccount>,
+    pub user_token_out: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// CHECK: Mint of token X in the pool
     pub token_x_mint: UncheckedAccount<'info>,
@@ -93,7 +131,13 @@ pub struct ExecuteSwap<'info> {
     pub host_fee_in: Option<UncheckedAccount<'info>>,
 
     /// Mint of the input token (for decimals in USD conversion)
-    pub token_in_mint: InterfaceAccount<'info, Mint>,
+    pub token_in_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Mint of the output token
+    pub token_out_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token program owning the output mint
+    pub token_out_program: Interface<'info, TokenInterface>,
 
     /// CHECK: Token program for token X
     pub token_x_program: Interface<'info, TokenInterface>,
@@ -102,11 +146,14 @@ pub struct ExecuteSwap<'info> {
     pub token_y_program: Interface<'info, TokenInterface>,
 
     /// CHECK: DLMM event authority PDA
-    pub event_authority: UncheckedAccount<'info>,
+    pub dlmm_event_authority: UncheckedAccount<'info>,
 
     /// CHECK: Meteora DLMM program
     #[account(address = dlmm::ID)]
     pub dlmm_program: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn execute_swap<'info>(
@@ -115,7 +162,7 @@ pub fn execute_swap<'info>(
     _nonce_delegate: u64,
     amount_in: u64,
     min_amount_out: u64,
-    feed_id: String,
+    feed_id: Option<String>,
 ) -> Result<()> {
     let clock = Clock::get()?;
 
@@ -125,25 +172,41 @@ pub fn execute_swap<'info>(
         ErrorCode::PermissionDenied
     );
 
-    // 2. Program policy check
+    // 2. Relayer, program and mint policy checks, on both sides of the swap
+    validate_relayer_policy(&ctx.accounts.vault, ctx.accounts.relayer.key)?;
     validate_program_policy(&ctx.accounts.vault, ctx.accounts.dlmm_program.key)?;
+    validate_mint_policy(&ctx.accounts.vault, &ctx.accounts.token_in_mint.key())?;
+    validate_mint_policy(&ctx.accounts.vault, &ctx.accounts.token_out_mint.key())?;
+    let blocked_extensions = &ctx.accounts.vault.policies.mints.blocked_extensions;
+    check_blocked_extensions(&ctx.accounts.token_in_mint.to_account_info(), blocked_extensions)?;
+    check_blocked_extensions(&ctx.accounts.token_out_mint.to_account_info(), blocked_extensions)?;
 
-    // 3. USD conversion — kept for future audit/logging use
+    // 3. USD conversion — charged against the vault's USD caps, rounded up
     let decimals = ctx.accounts.token_in_mint.decimals;
-    let _usd_spend_amount = calculate_usd_value(
-        &ctx.accounts.price_update,
-        &feed_id,
+    let oracles = OracleAccounts {
+        price_update: ctx.accounts.price_update.as_deref(),
+        switchboard_feed: ctx.accounts.switchboard_feed.as_deref(),
+    };
+    let valuation = calculate_usd_value(
+        &oracles,
+        &ctx.accounts.token_in_mint.key(),
+        feed_id.as_deref(),
         amount_in,
         decimals,
+        &ctx.accounts.vault.policies.oracle,
+        Rounding::Up,
     )?;
 
     // 4. Spending limits
     validate_and_update_limits(
         &mut ctx.accounts.vault,
         &mut ctx.accounts.delegate,
+        ctx.accounts.parent_delegate.as_deref_mut(),
+        CAN_SWAP,
         &clock,
         amount_in,
     )?;
+    charge_usd_limits(&mut ctx.accounts.vault, &[valuation.usd_value])?;
 
     // 5. Build vault PDA signer seeds
     let vault = &ctx.accounts.vault;
@@ -177,7 +240,7 @@ pub fn execute_swap<'info>(
         user: ctx.accounts.vault.to_account_info(),
         token_x_program: ctx.accounts.token_x_program.to_account_info(),
         token_y_program: ctx.accounts.token_y_program.to_account_info(),
-        event_authority: ctx.accounts.event_authority.to_account_info(),
+        event_authority: ctx.accounts.dlmm_event_authority.to_account_info(),
         program: ctx.accounts.dlmm_program.to_account_info(),
     };
 
@@ -192,6 +255,60 @@ pub fn execute_swap<'info>(
     // 8. Execute swap
     dlmm::cpi::swap(cpi_ctx, amount_in, min_amount_out)?;
 
-    msg!("Swap executed: {} tokens in, min {} out", amount_in, min_amount_out);
+    // List the output account if this swap created it. A full registry doesn't block the swap.
+    let token_out = VaultTokenAccount {
+        mint: ctx.accounts.token_out_mint.key(),
+        address: ctx.accounts.user_token_out.key(),
+        token_program: ctx.accounts.token_out_program.key(),
+    };
+    ctx.accounts.vault.register_token_account(token_out);
+
+    record_spend(
+        &ctx.accounts.vault,
+        ctx.accounts.spend_ledger.as_ref(),
+        SpendEntry {
+            slot: clock.slot,
+            amount: amount_in,
+            usd_value: valuation.usd_value,
+            delegate: ctx.accounts.delegate.key(),
+            mint: ctx.accounts.user_token_in.mint,
+            destination: ctx.accounts.user_token_out.key(),
+            route_kind: ROUTE_KIND_SWAP,
+            price_source: valuation.source as u8,
+            oracle_kind: valuation.oracle as u8,
+            _padding: [0; 5],
+        },
+    )?;
+
+    let relayer_fee = reimburse_relayer(
+        ctx.accounts.gas_tank.as_deref_mut(),
+        &ctx.accounts.relayer.to_account_info(),
+        &clock,
+    )?;
+
+    emit_cpi!(SwapExecuted {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+        relayer: ctx.accounts.relayer.key(),
+        mint_in: ctx.accounts.user_token_in.mint,
+        mint_out: ctx.accounts.user_token_out.mint,
+        amount_in,
+        min_amount_out,
+        usd_value: valuation.usd_value,
+        price_source: valuation.source,
+        oracle: valuation.oracle,
+        relayer_fee,
+        vault_daily_spent: ctx.accounts.vault.policies.spending.daily_spent,
+        vault_daily_usd_spent: ctx.accounts.vault.policies.spending.daily_usd_spent,
+        delegate_daily_spent: ctx.accounts.delegate.daily_spent,
+        delegate_uses: ctx.accounts.delegate.uses,
+    });
+
+    msg!(
+        "Swap executed: {} tokens in, min {} out, relayer {}",
+        amount_in,
+        min_amount_out,
+        ctx.accounts.relayer.key()
+    );
     Ok(())
 }
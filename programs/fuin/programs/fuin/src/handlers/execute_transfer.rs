@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
 
-use crate::{error::ErrorCode, state::{Delegate, Vault, delegate::CAN_TRANSFER}};
-use super::validate_and_update_limits;
+use crate::{error::ErrorCode, events::TransferExecuted, state::{Delegate, GasTank, OracleKind, PriceSource, SpendEntry, SpendLedger, Vault, delegate::CAN_TRANSFER, spend_ledger::ROUTE_KIND_TRANSFER}};
+use super::{attach_memo, record_spend, reimburse_relayer, transfer_lamports_from_vault, validate_and_update_limits, validate_memo_policy, validate_relayer_policy};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(nonce_vault:u64, nonce_delegate:u64)]
 pub struct ExecuteTransfer<'info>{
@@ -39,38 +41,105 @@ pub struct ExecuteTransfer<'info>{
     )]
     pub delegate: Account<'info, Delegate>,
 
+    /// Parent of a sub-delegate, required when `delegate.parent` is set
+    #[account(mut)]
+    pub parent_delegate: Option<Account<'info, Delegate>>,
+
+    /// Guardian-funded tank that reimburses the relayer, optional
+    #[account(
+        mut,
+        seeds = [b"gas_tank", vault.key().as_ref()],
+        bump = gas_tank.bump,
+    )]
+    pub gas_tank: Option<Account<'info, GasTank>>,
+
+    /// Audit ring buffer of recent executions, required once registered on the vault
+    #[account(
+        mut,
+        address = vault.spend_ledger.unwrap_or_default() @ErrorCode::InvalidSpendLedger,
+    )]
+    pub spend_ledger: Option<AccountLoader<'info, SpendLedger>>,
+
     /// CHECK: Destination address
     #[account(mut)]
     pub destination: AccountInfo<'info>,
 
     pub system_program: Program<'info, System>,
+
+    /// SPL Memo program, required when a memo is attached
+    pub memo_program: Option<Program<'info, Memo>>,
 }
 
-pub fn execute_transfer<'info>(ctx: Context<ExecuteTransfer>, _nonce_vault: u64, _nonce_delegate: u64, amount: u64)->Result<()>{
+pub fn execute_transfer(ctx: Context<ExecuteTransfer>, _nonce_vault: u64, _nonce_delegate: u64, amount: u64, memo: Option<String>)->Result<()>{
 
     let clock = Clock::get()?;
 
     // Permission check
     require!(ctx.accounts.delegate.has_permission(CAN_TRANSFER), ErrorCode::PermissionDenied);
 
+    // Relayer policy check
+    validate_relayer_policy(&ctx.accounts.vault, ctx.accounts.relayer.key)?;
+
     // NOTE: No program policy check here. SOL transfers use direct lamport
     // manipulation (no CPI), so the program allow/deny list doesn't apply.
     // Program policy is enforced in execute_spl_transfer and execute_swap
     // where actual CPI calls target external programs.
 
+    validate_memo_policy(&ctx.accounts.vault, amount, memo.as_deref())?;
+
     validate_and_update_limits(
         &mut ctx.accounts.vault,
         &mut ctx.accounts.delegate,
+        ctx.accounts.parent_delegate.as_mut(),
+        CAN_TRANSFER,
         &clock,
         amount
     )?;
 
-    let vault_info = ctx.accounts.vault.to_account_info();
-    let destination_info = ctx.accounts.destination.to_account_info();
+    attach_memo(ctx.accounts.memo_program.as_ref(), memo.as_deref())?;
+
+    transfer_lamports_from_vault(
+        &ctx.accounts.vault,
+        &ctx.accounts.destination,
+        amount,
+    )?;
 
-    **vault_info.try_borrow_mut_lamports()? -= amount;
-    **destination_info.try_borrow_mut_lamports()? += amount;
+    record_spend(
+        &ctx.accounts.vault,
+        ctx.accounts.spend_ledger.as_ref(),
+        SpendEntry {
+            slot: clock.slot,
+            amount,
+            usd_value: 0,
+            delegate: ctx.accounts.delegate.key(),
+            mint: Pubkey::default(),
+            destination: ctx.accounts.destination.key(),
+            route_kind: ROUTE_KIND_TRANSFER,
+            price_source: PriceSource::Spot as u8,
+            oracle_kind: OracleKind::Unpriced as u8,
+            _padding: [0; 5],
+        },
+    )?;
+
+    let relayer_fee = reimburse_relayer(
+        ctx.accounts.gas_tank.as_mut(),
+        &ctx.accounts.relayer.to_account_info(),
+        &clock,
+    )?;
 
-    msg!("Transfer executed: {} lamports", amount);
+    emit_cpi!(TransferExecuted {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+        relayer: ctx.accounts.relayer.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+        memo,
+        relayer_fee,
+        vault_daily_spent: ctx.accounts.vault.policies.spending.daily_spent,
+        delegate_daily_spent: ctx.accounts.delegate.daily_spent,
+        delegate_uses: ctx.accounts.delegate.uses,
+    });
+
+    msg!("Transfer executed: {} lamports, relayer {}", amount, ctx.accounts.relayer.key());
     Ok(())
 }
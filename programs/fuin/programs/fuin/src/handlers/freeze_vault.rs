@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::state::{Vault, VaultState};
+use crate::{events::{VaultFrozen, VaultUnfrozen}, state::{Vault, VaultState}};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(nonce: u64)]
 pub struct FreezeVault<'info> {
@@ -21,6 +22,7 @@ pub fn freeze_vault(ctx: Context<FreezeVault>, _nonce: u64) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     vault.state = VaultState::Frozen;
     vault.recovery.last_guardian_activity = clock.unix_timestamp;
+    emit_cpi!(VaultFrozen { vault: ctx.accounts.vault.key() });
     msg!("Vault frozen");
     Ok(())
 }
@@ -30,6 +32,7 @@ pub fn unfreeze_vault(ctx: Context<FreezeVault>, _nonce: u64) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     vault.state = VaultState::Active;
     vault.recovery.last_guardian_activity = clock.unix_timestamp;
+    emit_cpi!(VaultUnfrozen { vault: ctx.accounts.vault.key() });
     msg!("Vault unfrozen");
     Ok(())
 }
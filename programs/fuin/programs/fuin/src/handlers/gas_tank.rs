@@ -0,0 +1,167 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::{events::{GasTankClosed, GasTankUpdated}, state::{GasTank, Vault}};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct InitGasTank<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = guardian,
+        seeds = [b"gas_tank", vault.key().as_ref()],
+        bump,
+        space = GasTank::DISCRIMINATOR.len() + GasTank::INIT_SPACE,
+    )]
+    pub gas_tank: Account<'info, GasTank>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct ManageGasTank<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"gas_tank", vault.key().as_ref()],
+        bump = gas_tank.bump,
+        has_one = vault,
+    )]
+    pub gas_tank: Account<'info, GasTank>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CloseGasTank<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"gas_tank", vault.key().as_ref()],
+        bump = gas_tank.bump,
+        has_one = vault,
+        close = guardian,
+    )]
+    pub gas_tank: Account<'info, GasTank>,
+}
+
+pub fn init_gas_tank(ctx: Context<InitGasTank>, _nonce: u64, fee_per_call: u64, daily_cap: u64)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    let gas_tank = &mut ctx.accounts.gas_tank;
+    gas_tank.set_inner(GasTank {
+        vault: ctx.accounts.vault.key(),
+        fee_per_call,
+        daily_cap,
+        daily_spent: 0,
+        last_reset_epoch: clock.epoch,
+        bump: ctx.bumps.gas_tank,
+    });
+
+    emit_cpi!(GasTankUpdated {
+        vault: ctx.accounts.vault.key(),
+        fee_per_call: ctx.accounts.gas_tank.fee_per_call,
+        daily_cap: ctx.accounts.gas_tank.daily_cap,
+        balance: ctx.accounts.gas_tank.get_lamports(),
+    });
+
+    msg!("Gas tank initialized: {} lamports per call", fee_per_call);
+    Ok(())
+}
+
+pub fn fund_gas_tank(ctx: Context<ManageGasTank>, _nonce: u64, amount: u64)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.guardian.to_account_info(),
+            to: ctx.accounts.gas_tank.to_account_info(),
+        },
+    );
+    transfer(cpi_ctx, amount)?;
+
+    emit_cpi!(GasTankUpdated {
+        vault: ctx.accounts.vault.key(),
+        fee_per_call: ctx.accounts.gas_tank.fee_per_call,
+        daily_cap: ctx.accounts.gas_tank.daily_cap,
+        balance: ctx.accounts.gas_tank.get_lamports(),
+    });
+
+    msg!("Gas tank funded: {} lamports", amount);
+    Ok(())
+}
+
+pub fn update_gas_tank(ctx: Context<ManageGasTank>, _nonce: u64, new_fee_per_call: Option<u64>, new_daily_cap: Option<u64>)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    let gas_tank = &mut ctx.accounts.gas_tank;
+
+    if let Some(fee) = new_fee_per_call {
+        gas_tank.fee_per_call = fee;
+        msg!("Gas tank: Fee per call updated to {}", fee);
+    }
+
+    if let Some(cap) = new_daily_cap {
+        gas_tank.daily_cap = cap;
+        msg!("Gas tank: Daily cap updated to {}", cap);
+    }
+
+    emit_cpi!(GasTankUpdated {
+        vault: ctx.accounts.vault.key(),
+        fee_per_call: ctx.accounts.gas_tank.fee_per_call,
+        daily_cap: ctx.accounts.gas_tank.daily_cap,
+        balance: ctx.accounts.gas_tank.get_lamports(),
+    });
+
+    Ok(())
+}
+
+pub fn close_gas_tank(ctx: Context<CloseGasTank>, _nonce: u64)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    emit_cpi!(GasTankClosed { vault: ctx.accounts.vault.key() });
+
+    msg!("Gas tank closed, balance returned to guardian");
+    Ok(())
+}
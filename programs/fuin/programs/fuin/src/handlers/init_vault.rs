@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 
-use crate::state::vault::{Vault, VaultState, RecoveryConfig};
-use crate::state::policy::{PolicySet, SpendingPolicy, ProgramPolicy, TimePolicy, RiskPolicy};
+use crate::events::VaultInitialized;
+use crate::state::vault::{Vault, VaultState, RecoveryConfig, VAULT_VERSION};
+use crate::state::policy::{PolicySet, SpendingPolicy, ProgramPolicy, TimePolicy, RiskPolicy, RelayerPolicy, OraclePolicy, MintPolicy};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(nonce:u64)]
 pub struct InitializeVault<'info>{
@@ -31,7 +33,7 @@ pub fn init_vault(ctx: Context<InitializeVault>, nonce:u64, daily_cap: u64, per_
 
     let vault = &mut ctx.accounts.vault;
     vault.set_inner(Vault {
-        version: 1,
+        version: VAULT_VERSION,
         state: VaultState::Active,
         guardian: ctx.accounts.guardian.key(),
         policies: PolicySet {
@@ -40,6 +42,10 @@ pub fn init_vault(ctx: Context<InitializeVault>, nonce:u64, daily_cap: u64, per_
                 per_tx_cap,
                 daily_spent: 0,
                 last_reset_epoch: clock.epoch,
+                uses_per_batch: 0,
+                daily_usd_cap: 0,
+                per_tx_usd_cap: 0,
+                daily_usd_spent: 0,
             },
             programs: ProgramPolicy {
                 allow_list: allowed_programs,
@@ -52,6 +58,20 @@ pub fn init_vault(ctx: Context<InitializeVault>, nonce:u64, daily_cap: u64, per_
             risk: RiskPolicy {
                 max_slippage_bps: 0,
                 require_cosign_above: 0,
+                require_memo_above: 0,
+            },
+            relayers: RelayerPolicy {
+                allow_list: Vec::new(),
+            },
+            oracle: OraclePolicy {
+                feeds: Vec::new(),
+                mints: Vec::new(),
+                pegged: Vec::new(),
+            },
+            mints: MintPolicy {
+                allow_list: Vec::new(),
+                deny_list: Vec::new(),
+                blocked_extensions: Vec::new(),
             },
         },
         recovery: RecoveryConfig {
@@ -59,9 +79,21 @@ pub fn init_vault(ctx: Context<InitializeVault>, nonce:u64, daily_cap: u64, per_
             last_guardian_activity: clock.unix_timestamp,
             backup_guardian: None,
         },
+        token_accounts: Vec::new(),
+        committed_lamports: 0,
+        spend_ledger: None,
+        delegates_issued: 0,
         nonce,
         bump: ctx.bumps.vault,
     });
 
+    emit_cpi!(VaultInitialized {
+        vault: ctx.accounts.vault.key(),
+        guardian: ctx.accounts.guardian.key(),
+        nonce,
+        daily_cap,
+        per_tx_cap,
+    });
+
     Ok(())
 }
@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::state::{Delegate, Vault};
+use crate::{events::DelegateIssued, state::{Delegate, Vault}};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(vault_nonce: u64, delegate_nonce: u64)]
 pub struct IssueDelegate<'info>{
@@ -36,6 +37,7 @@ pub struct IssueDelegate<'info>{
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn issue_delegate(
     ctx: Context<IssueDelegate>,
     _vault_nonce: u64,
@@ -50,6 +52,7 @@ pub fn issue_delegate(
     let expiry = clock.unix_timestamp + validity_in_secs;
 
     ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+    let issuance = ctx.accounts.vault.issue_delegate()?;
 
     let delegate = &mut ctx.accounts.delegate;
     delegate.set_inner(Delegate {
@@ -63,10 +66,27 @@ pub fn issue_delegate(
         uses: 0,
         expiry,
         is_active: true,
+        allow_key_rotation: false,
+        parent: None,
+        issuance,
+        parent_issuance: 0,
+        intent_nonce_floor: 0,
+        intent_nonce_bitmap: 0,
         nonce: delegate_nonce,
         bump: ctx.bumps.delegate,
     });
 
+    emit_cpi!(DelegateIssued {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+        authority: delegate_key,
+        parent: None,
+        permissions,
+        daily_limit,
+        max_uses,
+        expiry,
+    });
+
     msg!("Delegate issued for {:?}", delegate_key);
     Ok(())
 }
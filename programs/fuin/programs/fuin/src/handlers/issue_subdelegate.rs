@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, events::DelegateIssued, state::{Delegate, Vault, VaultState, delegate::CAN_SUBDELEGATE}};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_parent: u64, nonce_child: u64)]
+pub struct IssueSubdelegate<'info>{
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub delegate_key: Signer<'info>,
+
+    /// CHECK: Validated via vault PDA seeds (vault is derived from guardian's key)
+    pub guardian: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key.as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        seeds = [
+            b"delegate",
+            vault.key().as_ref(),
+            &nonce_parent.to_le_bytes(),
+        ],
+        bump = parent_delegate.bump,
+        constraint = parent_delegate.vault == vault.key() @ErrorCode::InvalidSession,
+        constraint = parent_delegate.authority == delegate_key.key() @ErrorCode::InvalidSession,
+    )]
+    pub parent_delegate: Account<'info, Delegate>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            b"delegate",
+            vault.key().as_ref(),
+            &nonce_child.to_le_bytes(),
+        ],
+        bump,
+        space = Delegate::DISCRIMINATOR.len() + Delegate::INIT_SPACE,
+    )]
+    pub delegate: Account<'info, Delegate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn issue_subdelegate(
+    ctx: Context<IssueSubdelegate>,
+    _nonce_vault: u64,
+    _nonce_parent: u64,
+    nonce_child: u64,
+    delegate_key: Pubkey,
+    permissions: u16,
+    daily_limit: u64,
+    max_uses: u32,
+    validity_in_secs: i64,
+)->Result<()>{
+    let clock = Clock::get()?;
+    let parent = &ctx.accounts.parent_delegate;
+
+    require!(ctx.accounts.vault.state == VaultState::Active, ErrorCode::VaultFrozen);
+
+    // Parent must be usable and hold the sub-delegation bit
+    require!(parent.is_active, ErrorCode::DelegateInactive);
+    require!(clock.unix_timestamp <= parent.expiry, ErrorCode::DelegateExpired);
+    require!(parent.has_permission(CAN_SUBDELEGATE), ErrorCode::PermissionDenied);
+
+    // Only one level deep, so execute handlers only ever need the direct parent to check revocation
+    require!(parent.parent.is_none(), ErrorCode::SubdelegationTooDeep);
+
+    // Child permissions must be a strict subset and cannot re-delegate
+    require!(permissions & CAN_SUBDELEGATE == 0, ErrorCode::InvalidSubdelegateScope);
+    require!(permissions & !parent.permissions == 0, ErrorCode::InvalidSubdelegateScope);
+
+    // A limit of 0 means unlimited, so a limited parent cannot hand out an unlimited child
+    if parent.daily_limit > 0 {
+        require!(daily_limit > 0 && daily_limit <= parent.daily_limit, ErrorCode::InvalidSubdelegateScope);
+    }
+
+    if parent.max_uses > 0 {
+        let remaining_uses = parent.max_uses.saturating_sub(parent.uses);
+        require!(max_uses > 0 && max_uses <= remaining_uses, ErrorCode::InvalidSubdelegateScope);
+    }
+
+    let expiry = clock.unix_timestamp
+        .checked_add(validity_in_secs)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(expiry <= parent.expiry, ErrorCode::InvalidSubdelegateScope);
+
+    let parent_key = parent.key();
+    let parent_issuance = parent.issuance;
+    let issuance = ctx.accounts.vault.issue_delegate()?;
+    let delegate = &mut ctx.accounts.delegate;
+    delegate.set_inner(Delegate {
+        vault: ctx.accounts.vault.key(),
+        authority: delegate_key,
+        permissions,
+        daily_limit,
+        daily_spent: 0,
+        last_reset_epoch: clock.epoch,
+        max_uses,
+        uses: 0,
+        expiry,
+        is_active: true,
+        allow_key_rotation: false,
+        parent: Some(parent_key),
+        issuance,
+        parent_issuance,
+        intent_nonce_floor: 0,
+        intent_nonce_bitmap: 0,
+        nonce: nonce_child,
+        bump: ctx.bumps.delegate,
+    });
+
+    emit_cpi!(DelegateIssued {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+        authority: delegate_key,
+        parent: Some(parent_key),
+        permissions,
+        daily_limit,
+        max_uses,
+        expiry,
+    });
+
+    msg!("Sub-delegate issued for {:?} under {:?}", delegate_key, parent_key);
+    Ok(())
+}
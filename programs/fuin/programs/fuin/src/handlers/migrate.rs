@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::{error::ErrorCode, events::{DelegateMigrated, VaultMigrated}, state::{Delegate, LegacyDelegate, LegacyVault, Vault, VAULT_VERSION}};
+
+// Each migration grows the account in a single realloc
+const _: () = assert!(Vault::INIT_SPACE - LegacyVault::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+const _: () = assert!(Delegate::INIT_SPACE - LegacyDelegate::INIT_SPACE <= MAX_PERMITTED_DATA_INCREASE);
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct MigrateVault<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    /// CHECK: still in the legacy layout, so it can't be loaded as `Vault`. Seeds tie it to
+    /// the guardian, the handler checks the discriminator and version before rewriting it.
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_delegate: u64)]
+pub struct MigrateDelegate<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", guardian.key().as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+        constraint = vault.version == VAULT_VERSION @ErrorCode::VaultNotMigrated,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// CHECK: still in the legacy layout, so it can't be loaded as `Delegate`. Seeds tie it to
+    /// the vault, the handler checks the discriminator and length before rewriting it.
+    #[account(
+        mut,
+        seeds = [b"delegate", vault.key().as_ref(), &nonce_delegate.to_le_bytes()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub delegate: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_vault(ctx: Context<MigrateVault>, _nonce: u64)->Result<()>{
+    let legacy = {
+        let data = ctx.accounts.vault.try_borrow_data()?;
+        require!(
+            data.len() > Vault::DISCRIMINATOR.len() && data.starts_with(Vault::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(data[Vault::DISCRIMINATOR.len()] < VAULT_VERSION, ErrorCode::AlreadyMigrated);
+        LegacyVault::deserialize(&mut &data[Vault::DISCRIMINATOR.len()..])?
+    };
+    require_keys_eq!(legacy.guardian, ctx.accounts.guardian.key(), anchor_lang::error::ErrorCode::ConstraintHasOne);
+    let from_version = legacy.version;
+
+    let mut vault = Vault::from(legacy);
+    vault.recovery.last_guardian_activity = Clock::get()?.unix_timestamp;
+
+    let top_up = rewrite_account(
+        &ctx.accounts.guardian,
+        ctx.accounts.vault.as_ref(),
+        &ctx.accounts.system_program,
+        &vault,
+        Vault::DISCRIMINATOR.len() + Vault::INIT_SPACE,
+    )?;
+
+    emit_cpi!(VaultMigrated {
+        vault: ctx.accounts.vault.key(),
+        from_version,
+        to_version: VAULT_VERSION,
+        rent_top_up: top_up,
+    });
+
+    msg!("Vault migrated from version {} to {}", from_version, VAULT_VERSION);
+    Ok(())
+}
+
+pub fn migrate_delegate(ctx: Context<MigrateDelegate>, _nonce_vault: u64, _nonce_delegate: u64)->Result<()>{
+    let new_len = Delegate::DISCRIMINATOR.len() + Delegate::INIT_SPACE;
+    let legacy = {
+        let data = ctx.accounts.delegate.try_borrow_data()?;
+        require!(
+            data.starts_with(Delegate::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        // Delegates carry no version, the legacy layout is the only shorter one
+        require!(data.len() < new_len, ErrorCode::AlreadyMigrated);
+        LegacyDelegate::deserialize(&mut &data[Delegate::DISCRIMINATOR.len()..])?
+    };
+    require_keys_eq!(legacy.vault, ctx.accounts.vault.key(), anchor_lang::error::ErrorCode::ConstraintHasOne);
+
+    let delegate = Delegate::from(legacy);
+    let top_up = rewrite_account(
+        &ctx.accounts.guardian,
+        ctx.accounts.delegate.as_ref(),
+        &ctx.accounts.system_program,
+        &delegate,
+        new_len,
+    )?;
+
+    emit_cpi!(DelegateMigrated {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+        rent_top_up: top_up,
+    });
+
+    msg!("Delegate migrated to the current layout");
+    Ok(())
+}
+
+// Grows `account` to `new_len` and writes `value` over it. The guardian pays the extra rent,
+// so the vault's spendable lamports are unchanged. Returns the lamports topped up.
+fn rewrite_account<'info, T: AccountSerialize>(
+    guardian: &Signer<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    value: &T,
+    new_len: usize,
+)->Result<u64>{
+    let rent = Rent::get()?;
+    let top_up = rent
+        .minimum_balance(new_len)
+        .saturating_sub(rent.minimum_balance(account.data_len()));
+    if top_up > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: guardian.to_account_info(),
+                to: account.clone(),
+            },
+        );
+        transfer(cpi_ctx, top_up)?;
+    }
+
+    account.resize(new_len)?;
+    let mut data = account.try_borrow_mut_data()?;
+    data.fill(0);
+    value.try_serialize(&mut &mut data[..])?;
+
+    Ok(top_up)
+}
@@ -27,3 +27,48 @@ pub use freeze_vault::*;
 
 pub mod execute_swap;
 pub use execute_swap::*;
+
+pub mod close_delegate;
+pub use close_delegate::*;
+
+pub mod update_delegate;
+pub use update_delegate::*;
+
+pub mod rotate_delegate_key;
+pub use rotate_delegate_key::*;
+
+pub mod migrate;
+pub use migrate::*;
+
+pub mod issue_subdelegate;
+pub use issue_subdelegate::*;
+
+pub mod execute_intent;
+pub use execute_intent::*;
+
+pub mod gas_tank;
+pub use gas_tank::*;
+
+pub mod update_policy;
+pub use update_policy::*;
+
+pub mod spend_ledger;
+pub use spend_ledger::*;
+
+pub mod withdraw_spl;
+pub use withdraw_spl::*;
+
+pub mod deposit;
+pub use deposit::*;
+
+pub mod token_account;
+pub use token_account::*;
+
+pub mod execute_batch;
+pub use execute_batch::*;
+
+pub mod schedule;
+pub use schedule::*;
+
+pub mod stream;
+pub use stream::*;
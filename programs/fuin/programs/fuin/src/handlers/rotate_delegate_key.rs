@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, events::DelegateKeyRotated, state::{Delegate, Vault}};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_delegate: u64)]
+pub struct RotateDelegateKey<'info>{
+    pub delegate_key: Signer<'info>,
+
+    pub new_delegate_key: Signer<'info>,
+
+    /// CHECK: Validated via vault PDA seeds (vault is derived from guardian's key)
+    pub guardian: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"vault", guardian.key.as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"delegate",
+            vault.key().as_ref(),
+            &nonce_delegate.to_le_bytes(),
+        ],
+        bump = delegate.bump,
+        constraint = delegate.vault == vault.key() @ErrorCode::InvalidSession,
+        constraint = delegate.authority == delegate_key.key() @ErrorCode::InvalidSession,
+    )]
+    pub delegate: Account<'info, Delegate>,
+}
+
+pub fn rotate_delegate_key(ctx: Context<RotateDelegateKey>, _nonce_vault: u64, _nonce_delegate: u64)->Result<()>{
+    let clock = Clock::get()?;
+    let new_key = ctx.accounts.new_delegate_key.key();
+    let delegate = &mut ctx.accounts.delegate;
+
+    require!(delegate.allow_key_rotation, ErrorCode::KeyRotationDisabled);
+    require!(delegate.is_active, ErrorCode::DelegateInactive);
+    require!(clock.unix_timestamp <= delegate.expiry, ErrorCode::DelegateExpired);
+    require!(new_key != delegate.authority, ErrorCode::InvalidAction);
+
+    // Only the authority moves, spent counters and uses stay on the PDA
+    let old_key = delegate.authority;
+    delegate.authority = new_key;
+
+    emit_cpi!(DelegateKeyRotated {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+        old_authority: old_key,
+        new_authority: new_key,
+    });
+
+    msg!("Delegate key rotated to {:?}", new_key);
+    Ok(())
+}
@@ -0,0 +1,319 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{
+    error::ErrorCode,
+    events::{ScheduleCancelled, ScheduleCreated, SchedulePaymentExecuted},
+    pricing::{calculate_usd_value, OracleAccounts, Rounding, UsdValuation},
+    state::{spend_ledger::ROUTE_KIND_SCHEDULE, OracleKind, PriceSource, Schedule, SpendEntry, SpendLedger, Vault},
+};
+use super::{charge_usd_limits, charge_vault_limits, record_spend, require_rent_exempt_after, transfer_lamports_from_vault, transfer_spl_from_vault, validate_program_policy};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_schedule: u64)]
+pub struct CreateSchedule<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = guardian,
+        seeds = [b"schedule", vault.key().as_ref(), &nonce_schedule.to_le_bytes()],
+        bump,
+        space = Schedule::DISCRIMINATOR.len() + Schedule::INIT_SPACE,
+    )]
+    pub schedule: Account<'info, Schedule>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_schedule: u64)]
+pub struct CancelSchedule<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"schedule", vault.key().as_ref(), &nonce_schedule.to_le_bytes()],
+        bump = schedule.bump,
+        has_one = vault,
+        close = guardian,
+    )]
+    pub schedule: Account<'info, Schedule>,
+}
+
+/// SPL schedules also need `mint`, `vault_token_account` and `token_program`,
+/// and `destination` is the recipient's associated token account. Payouts are charged against
+/// the USD caps, so the oracle accounts are needed when the vault prices the mint. Remaining
+/// accounts are forwarded as the mint's transfer hook extra accounts.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_schedule: u64)]
+pub struct CrankSchedule<'info>{
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Validated via vault PDA seeds (vault is derived from guardian's key)
+    pub guardian: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key.as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"schedule", vault.key().as_ref(), &nonce_schedule.to_le_bytes()],
+        bump = schedule.bump,
+        has_one = vault,
+    )]
+    pub schedule: Account<'info, Schedule>,
+
+    /// CHECK: Checked against the schedule's recipient in the handler
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// Pyth price update, required for SPL payouts of a mint the vault prices with Pyth
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+
+    /// CHECK: Switchboard On-Demand pull feed, key and owner are checked against the oracle policy
+    pub switchboard_feed: Option<UncheckedAccount<'info>>,
+
+    /// Audit ring buffer of recent executions, required once registered on the vault
+    #[account(
+        mut,
+        address = vault.spend_ledger.unwrap_or_default() @ErrorCode::InvalidSpendLedger,
+    )]
+    pub spend_ledger: Option<AccountLoader<'info, SpendLedger>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_schedule(
+    ctx: Context<CreateSchedule>,
+    _nonce_vault: u64,
+    nonce_schedule: u64,
+    recipient: Pubkey,
+    mint: Option<Pubkey>,
+    amount: u64,
+    interval_seconds: i64,
+    start_time: i64,
+    end_time: i64,
+    max_payments: u32,
+)->Result<()>{
+    let clock = Clock::get()?;
+
+    // A start in the past makes the first payment due right away
+    let start_time = start_time.max(clock.unix_timestamp);
+
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(interval_seconds > 0, ErrorCode::InvalidSchedule);
+    require!(end_time == 0 || end_time >= start_time, ErrorCode::InvalidSchedule);
+
+    let mint = mint.unwrap_or_default();
+
+    ctx.accounts.schedule.set_inner(Schedule {
+        vault: ctx.accounts.vault.key(),
+        recipient,
+        mint,
+        amount,
+        interval_seconds,
+        start_time,
+        end_time,
+        max_payments,
+        payments_made: 0,
+        next_payment_at: start_time,
+        nonce: nonce_schedule,
+        bump: ctx.bumps.schedule,
+    });
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    emit_cpi!(ScheduleCreated {
+        vault: ctx.accounts.vault.key(),
+        schedule: ctx.accounts.schedule.key(),
+        recipient,
+        mint,
+        amount,
+        interval_seconds,
+        start_time,
+    });
+
+    msg!("Schedule created: {} every {}s to {}", amount, interval_seconds, recipient);
+    Ok(())
+}
+
+pub fn cancel_schedule(ctx: Context<CancelSchedule>, _nonce_vault: u64, _nonce_schedule: u64)->Result<()>{
+    ctx.accounts.vault.recovery.last_guardian_activity = Clock::get()?.unix_timestamp;
+
+    emit_cpi!(ScheduleCancelled {
+        vault: ctx.accounts.vault.key(),
+        schedule: ctx.accounts.schedule.key(),
+        payments_made: ctx.accounts.schedule.payments_made,
+    });
+
+    msg!("Schedule cancelled after {} payments", ctx.accounts.schedule.payments_made);
+    Ok(())
+}
+
+// Pays the next due payment. Missed payments are caught up one crank at a time,
+// each counting toward the vault's caps. Fails while the vault is frozen.
+pub fn crank_schedule<'info>(
+    ctx: Context<'_, '_, '_, 'info, CrankSchedule<'info>>,
+    _nonce_vault: u64,
+    _nonce_schedule: u64,
+)->Result<()>{
+    let clock = Clock::get()?;
+    let schedule = &ctx.accounts.schedule;
+
+    require!(!schedule.is_complete(), ErrorCode::ScheduleComplete);
+    require!(clock.unix_timestamp >= schedule.next_payment_at, ErrorCode::ScheduleNotDue);
+
+    let amount = schedule.amount;
+    let recipient = schedule.recipient;
+    let mint_key = schedule.mint;
+
+    charge_vault_limits(&mut ctx.accounts.vault, &clock, amount)?;
+
+    let mut valuation = UsdValuation {
+        usd_value: 0,
+        source: PriceSource::Spot,
+        oracle: OracleKind::Unpriced,
+    };
+    if mint_key == Pubkey::default() {
+        require_keys_eq!(ctx.accounts.destination.key(), recipient, ErrorCode::InvalidDestination);
+        require_rent_exempt_after(&ctx.accounts.destination.to_account_info(), amount)?;
+        transfer_lamports_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.destination.to_account_info(),
+            amount,
+        )?;
+    } else {
+        let (Some(mint), Some(vault_token_account), Some(token_program)) = (
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.vault_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        ) else {
+            return err!(ErrorCode::MissingTokenAccounts);
+        };
+
+        require_keys_eq!(mint.key(), mint_key, ErrorCode::MintMismatch);
+        validate_program_policy(&ctx.accounts.vault, &token_program.key())?;
+        require_keys_eq!(
+            vault_token_account.key(),
+            get_associated_token_address_with_program_id(&ctx.accounts.vault.key(), &mint_key, &token_program.key()),
+            ErrorCode::NonCanonicalTokenAccount
+        );
+        require_keys_eq!(
+            ctx.accounts.destination.key(),
+            get_associated_token_address_with_program_id(&recipient, &mint_key, &token_program.key()),
+            ErrorCode::InvalidDestination
+        );
+
+        // Valued from the vault's own oracle policy or pegged list, the cranker supplies no feed id.
+        // A mint the vault can't price is only paid out while no USD cap is set.
+        let oracle_policy = &ctx.accounts.vault.policies.oracle;
+        let spending = &ctx.accounts.vault.policies.spending;
+        let priced = oracle_policy.mint_oracle(&mint_key).is_some()
+            || oracle_policy.pegged_mint(&mint_key).is_some()
+            || spending.daily_usd_cap > 0
+            || spending.per_tx_usd_cap > 0;
+        if priced {
+            let oracles = OracleAccounts {
+                price_update: ctx.accounts.price_update.as_deref(),
+                switchboard_feed: ctx.accounts.switchboard_feed.as_deref(),
+            };
+            valuation = calculate_usd_value(
+                &oracles,
+                &mint_key,
+                None,
+                amount,
+                mint.decimals,
+                oracle_policy,
+                Rounding::Up,
+            )?;
+            charge_usd_limits(&mut ctx.accounts.vault, &[valuation.usd_value])?;
+        }
+
+        transfer_spl_from_vault(
+            &ctx.accounts.vault,
+            &token_program.to_account_info(),
+            &vault_token_account.to_account_info(),
+            &mint.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            mint.decimals,
+        )?;
+    }
+
+    record_spend(
+        &ctx.accounts.vault,
+        ctx.accounts.spend_ledger.as_ref(),
+        SpendEntry {
+            slot: clock.slot,
+            amount,
+            usd_value: valuation.usd_value,
+            delegate: ctx.accounts.schedule.key(),
+            mint: mint_key,
+            destination: ctx.accounts.destination.key(),
+            route_kind: ROUTE_KIND_SCHEDULE,
+            price_source: valuation.source as u8,
+            oracle_kind: valuation.oracle as u8,
+            _padding: [0; 5],
+        },
+    )?;
+
+    let schedule = &mut ctx.accounts.schedule;
+    schedule.payments_made = schedule.payments_made.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    schedule.next_payment_at = schedule
+        .next_payment_at
+        .checked_add(schedule.interval_seconds)
+        .ok_or(ErrorCode::Overflow)?;
+
+    emit_cpi!(SchedulePaymentExecuted {
+        vault: ctx.accounts.vault.key(),
+        schedule: ctx.accounts.schedule.key(),
+        cranker: ctx.accounts.cranker.key(),
+        recipient,
+        mint: mint_key,
+        amount,
+        usd_value: valuation.usd_value,
+        payments_made: ctx.accounts.schedule.payments_made,
+        next_payment_at: ctx.accounts.schedule.next_payment_at,
+        vault_daily_spent: ctx.accounts.vault.policies.spending.daily_spent,
+        vault_daily_usd_spent: ctx.accounts.vault.policies.spending.daily_usd_spent,
+    });
+
+    msg!("Scheduled payment {} executed: {}", ctx.accounts.schedule.payments_made, amount);
+    Ok(())
+}
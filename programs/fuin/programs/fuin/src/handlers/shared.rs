@@ -1,30 +1,66 @@
 use anchor_lang::prelude::*;
+use anchor_spl::{
+    memo::{build_memo, BuildMemo, Memo},
+    token_2022::spl_token_2022,
+};
 
-use crate::{error::ErrorCode, state::{LimitTracker, Delegate, Vault, VaultState}};
+use crate::{error::ErrorCode, state::{LimitTracker, Delegate, GasTank, SpendEntry, SpendLedger, Vault, VaultState}};
 
 pub fn validate_and_update_limits(
     vault: &mut Account<Vault>,
     delegate: &mut Account<Delegate>,
+    parent: Option<&mut Account<Delegate>>,
+    permission: u16,
     clock: &Clock,
     amount: u64,
+)->Result<()>{
+    validate_and_update_batch_limits(vault, delegate, parent, permission, clock, &[amount], 1)
+}
+
+/// Per-transaction checks run on each amount, daily limits are charged the total
+/// and the delegate (and its parent) is charged `uses` uses. The parent must also hold `permission`.
+pub fn validate_and_update_batch_limits(
+    vault: &mut Account<Vault>,
+    delegate: &mut Account<Delegate>,
+    parent: Option<&mut Account<Delegate>>,
+    permission: u16,
+    clock: &Clock,
+    amounts: &[u64],
+    uses: u32,
 )->Result<()>{
     // Check vault is active
     require!(vault.state == VaultState::Active, ErrorCode::VaultFrozen);
 
-    // Check delegate is active
-    require!(delegate.is_active, ErrorCode::DelegateInactive);
+    validate_delegate(delegate, clock, uses)?;
 
-    // Check delegate hasn't expired
-    require!(clock.unix_timestamp <= delegate.expiry, ErrorCode::DelegateExpired);
+    // Sub-delegates are only valid while their parent is, and spend from its budget too
+    let parent = match delegate.parent {
+        Some(parent_key) => {
+            let parent = parent.ok_or(ErrorCode::InvalidParentDelegate)?;
+            require_keys_eq!(parent.key(), parent_key, ErrorCode::InvalidParentDelegate);
+            // A parent closed and reissued at the same address doesn't revive its old children
+            require!(parent.issuance == delegate.parent_issuance, ErrorCode::InvalidParentDelegate);
+            validate_delegate(parent, clock, uses)?;
+            // Narrowing the parent's permissions narrows its children too
+            require!(parent.has_permission(permission), ErrorCode::PermissionDenied);
+            Some(parent)
+        },
+        None => None,
+    };
 
-    // Check max uses
-    if delegate.max_uses > 0 {
-        require!(delegate.uses < delegate.max_uses, ErrorCode::MaxUsesExceeded);
-    }
+    let mut amount: u64 = 0;
+    for &item in amounts {
+        // Per-transaction cap check
+        if vault.policies.spending.per_tx_cap > 0 {
+            require!(item <= vault.policies.spending.per_tx_cap, ErrorCode::PerTxLimitExceeded);
+        }
 
-    // Per-transaction cap check
-    if vault.policies.spending.per_tx_cap > 0 {
-        require!(amount <= vault.policies.spending.per_tx_cap, ErrorCode::PerTxLimitExceeded);
+        // Risk policy check — cosign
+        if vault.policies.risk.require_cosign_above > 0 && item > vault.policies.risk.require_cosign_above {
+            return err!(ErrorCode::RequiresGuardianCosign);
+        }
+
+        amount = amount.checked_add(item).ok_or(ErrorCode::Overflow)?;
     }
 
     // Time policy check
@@ -36,33 +72,95 @@ pub fn validate_and_update_limits(
         require!(clock.unix_timestamp <= time.allowed_before, ErrorCode::OutsideAllowedTime);
     }
 
-    // Risk policy check — cosign
-    if vault.policies.risk.require_cosign_above > 0 && amount > vault.policies.risk.require_cosign_above {
-        return err!(ErrorCode::RequiresGuardianCosign);
+    reset_vault_epoch(vault, clock);
+
+    // Limit Check & Update (Using the Trait)
+    vault.check_and_spend(amount)?;
+    spend_delegate(delegate, clock, amount, uses)?;
+    if let Some(parent) = parent {
+        spend_delegate(parent, clock, amount, uses)?;
     }
 
-    // Vault epoch reset
+    Ok(())
+}
+
+/// Vault-level caps only, for payments the guardian set up in advance (no delegate involved)
+pub fn charge_vault_limits(vault: &mut Account<Vault>, clock: &Clock, amount: u64) -> Result<()> {
+    require!(vault.state == VaultState::Active, ErrorCode::VaultFrozen);
+
+    if vault.policies.spending.per_tx_cap > 0 {
+        require!(amount <= vault.policies.spending.per_tx_cap, ErrorCode::PerTxLimitExceeded);
+    }
+
+    reset_vault_epoch(vault, clock);
+    vault.check_and_spend(amount)
+}
+
+/// Charges the vault's USD caps with the oracle valuation of each spend. Runs after the
+/// token-unit limits, which have already rolled the vault over to the current epoch.
+pub fn charge_usd_limits(vault: &mut Vault, usd_values: &[u64]) -> Result<()> {
+    let spending = &mut vault.policies.spending;
+
+    let mut total: u64 = 0;
+    for &usd_value in usd_values {
+        if spending.per_tx_usd_cap > 0 {
+            require!(usd_value <= spending.per_tx_usd_cap, ErrorCode::UsdPerTxLimitExceeded);
+        }
+        total = total.checked_add(usd_value).ok_or(ErrorCode::Overflow)?;
+    }
+
+    let spent = spending.daily_usd_spent.checked_add(total).ok_or(ErrorCode::Overflow)?;
+    if spending.daily_usd_cap > 0 {
+        require!(spent <= spending.daily_usd_cap, ErrorCode::UsdDailyLimitExceeded);
+    }
+    spending.daily_usd_spent = spent;
+    Ok(())
+}
+
+fn reset_vault_epoch(vault: &mut Vault, clock: &Clock) {
     if clock.epoch > vault.policies.spending.last_reset_epoch {
         vault.policies.spending.daily_spent = 0;
+        vault.policies.spending.daily_usd_spent = 0;
         vault.policies.spending.last_reset_epoch = clock.epoch;
     }
+}
 
+fn validate_delegate(delegate: &Delegate, clock: &Clock, uses: u32) -> Result<()> {
+    // Check delegate is active
+    require!(delegate.is_active, ErrorCode::DelegateInactive);
+
+    // Check delegate hasn't expired
+    require!(clock.unix_timestamp <= delegate.expiry, ErrorCode::DelegateExpired);
+
+    // Check max uses
+    if delegate.max_uses > 0 {
+        require!(delegate.uses.saturating_add(uses) <= delegate.max_uses, ErrorCode::MaxUsesExceeded);
+    }
+
+    Ok(())
+}
+
+fn spend_delegate(delegate: &mut Delegate, clock: &Clock, amount: u64, uses: u32) -> Result<()> {
     // Delegate epoch reset
     if clock.epoch > delegate.last_reset_epoch {
         delegate.daily_spent = 0;
         delegate.last_reset_epoch = clock.epoch;
     }
 
-    // Limit Check & Update (Using the Trait)
-    vault.check_and_spend(amount)?;
     delegate.check_and_spend(amount)?;
 
     // Increment uses
-    delegate.uses += 1;
+    delegate.uses = delegate.uses.checked_add(uses).ok_or(ErrorCode::Overflow)?;
 
     Ok(())
 }
 
+/// Replay protection for signed intents, run before any limits are touched
+pub fn validate_intent(delegate: &mut Delegate, clock: &Clock, nonce: u64, deadline: i64) -> Result<()> {
+    require!(clock.unix_timestamp <= deadline, ErrorCode::IntentExpired);
+    delegate.consume_intent_nonce(nonce)
+}
+
 pub fn validate_program_policy(vault: &Account<Vault>, target_program: &Pubkey) -> Result<()> {
     let programs = &vault.policies.programs;
 
@@ -78,3 +176,197 @@ pub fn validate_program_policy(vault: &Account<Vault>, target_program: &Pubkey)
 
     Ok(())
 }
+
+// Same rules as the program policy: deny list wins, a non-empty allow list is exhaustive
+pub fn validate_mint_policy(vault: &Account<Vault>, mint: &Pubkey) -> Result<()> {
+    let mints = &vault.policies.mints;
+
+    if mints.deny_list.contains(mint) {
+        return err!(ErrorCode::MintNotAllowed);
+    }
+
+    if !mints.allow_list.is_empty() && !mints.allow_list.contains(mint) {
+        return err!(ErrorCode::MintNotAllowed);
+    }
+
+    Ok(())
+}
+
+// Intents carry no memo, so relayed transfers above the threshold are rejected too
+pub fn validate_memo_policy(vault: &Account<Vault>, amount: u64, memo: Option<&str>) -> Result<()> {
+    let threshold = vault.policies.risk.require_memo_above;
+    if threshold > 0 && amount > threshold && memo.is_none() {
+        return err!(ErrorCode::MemoRequired);
+    }
+
+    Ok(())
+}
+
+pub fn validate_relayer_policy(vault: &Account<Vault>, relayer: &Pubkey) -> Result<()> {
+    let allow_list = &vault.policies.relayers.allow_list;
+
+    // Empty list keeps relaying permissionless
+    if !allow_list.is_empty() && !allow_list.contains(relayer) {
+        return err!(ErrorCode::RelayerNotAllowed);
+    }
+
+    Ok(())
+}
+
+/// Pays out of the vault's uncommitted balance, lamports owed to streams stay put
+pub fn transfer_lamports_from_vault(vault: &Account<Vault>, destination: &AccountInfo, amount: u64) -> Result<()> {
+    require!(amount <= available_lamports(vault)?, ErrorCode::InsufficientFunds);
+    send_lamports(&vault.to_account_info(), destination, amount)
+}
+
+/// Pays out lamports the vault committed to a stream and releases the commitment
+pub fn release_committed_lamports(vault: &mut Account<Vault>, destination: &AccountInfo, amount: u64) -> Result<()> {
+    vault.committed_lamports = vault.committed_lamports.checked_sub(amount).ok_or(ErrorCode::Overflow)?;
+    send_lamports(&vault.to_account_info(), destination, amount)
+}
+
+/// Stream and schedule payouts go to a wallet that may hold nothing yet, and the runtime rejects a
+/// transaction that leaves a new account below rent exemption
+pub fn require_rent_exempt_after(destination: &AccountInfo, amount: u64) -> Result<()> {
+    require!(is_rent_exempt_after(destination, amount)?, ErrorCode::RecipientNotRentExempt);
+    Ok(())
+}
+
+pub fn is_rent_exempt_after(destination: &AccountInfo, amount: u64) -> Result<bool> {
+    let balance = destination.lamports().checked_add(amount).ok_or(ErrorCode::Overflow)?;
+    Ok(balance >= Rent::get()?.minimum_balance(destination.data_len()))
+}
+
+/// Spendable lamports not committed to streams
+pub fn available_lamports(vault: &Account<Vault>) -> Result<u64> {
+    Ok(spendable_lamports(&vault.to_account_info())?.saturating_sub(vault.committed_lamports))
+}
+
+fn send_lamports(vault: &AccountInfo, destination: &AccountInfo, amount: u64) -> Result<()> {
+    // Paying the vault from itself would be a no-op that still counts against limits
+    require_keys_neq!(*vault.key, *destination.key, ErrorCode::InvalidDestination);
+    // The runtime refuses lamport changes on executable accounts, fail clearly up front.
+    // Program-owned data accounts (PDAs, escrows) are fine, crediting them needs no ownership.
+    require!(!destination.executable, ErrorCode::InvalidDestination);
+
+    // The vault has to stay rent exempt after paying out
+    require!(amount <= spendable_lamports(vault)?, ErrorCode::InsufficientFunds);
+    move_lamports(vault, destination, amount)
+}
+
+/// Lamports held above the account's rent-exempt minimum
+pub fn spendable_lamports(info: &AccountInfo) -> Result<u64> {
+    let rent_exempt = Rent::get()?.minimum_balance(info.data_len());
+    Ok(info.lamports().saturating_sub(rent_exempt))
+}
+
+// Checked lamport move, `from` must be owned by this program
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let from_balance = from.lamports().checked_sub(amount).ok_or(ErrorCode::InsufficientFunds)?;
+    let to_balance = to.lamports().checked_add(amount).ok_or(ErrorCode::Overflow)?;
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+    Ok(())
+}
+
+/// `transfer_checked` out of a vault-owned token account, signed with the vault PDA seeds.
+/// `additional_accounts` carries a Token-2022 transfer hook's extra account metas, empty otherwise.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_spl_from_vault<'info>(
+    vault: &Account<'info, Vault>,
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    additional_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    let seeds: &[&[&[u8]]] = &[&[
+        b"vault",
+        vault.guardian.as_ref(),
+        &vault.nonce.to_le_bytes(),
+        &[vault.bump],
+    ]];
+
+    // Resolves the hook's validation account and extra metas from `additional_accounts`,
+    // and is a plain transfer_checked for the Token program
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program.key,
+        from.clone(),
+        mint.clone(),
+        to.clone(),
+        vault.to_account_info(),
+        additional_accounts,
+        amount,
+        decimals,
+        seeds,
+    )?;
+
+    Ok(())
+}
+
+pub const MAX_MEMO_LEN: usize = 128;
+
+/// CPIs the memo to the SPL Memo program, if one was given. Runs right before the
+/// transfer so Token-2022 accounts that require incoming memos accept it.
+/// The Memo program moves no funds, so this CPI skips the vault's program policy.
+pub fn attach_memo<'info>(memo_program: Option<&Program<'info, Memo>>, memo: Option<&str>) -> Result<()> {
+    let Some(memo) = memo else {
+        return Ok(());
+    };
+    require!(!memo.is_empty() && memo.len() <= MAX_MEMO_LEN, ErrorCode::InvalidMemo);
+
+    let memo_program = memo_program.ok_or(ErrorCode::MissingMemoProgram)?;
+    build_memo(
+        CpiContext::new(memo_program.to_account_info(), BuildMemo {}),
+        memo.as_bytes(),
+    )
+}
+
+/// Pays the relayer a flat fee out of the vault's gas tank, if one was supplied.
+/// Returns the lamports paid out.
+pub fn reimburse_relayer(
+    gas_tank: Option<&mut Account<GasTank>>,
+    relayer: &AccountInfo,
+    clock: &Clock,
+) -> Result<u64> {
+    let Some(gas_tank) = gas_tank else {
+        return Ok(0);
+    };
+
+    let fee = gas_tank.fee_per_call;
+    if fee == 0 {
+        return Ok(0);
+    }
+
+    // Tank epoch reset
+    if clock.epoch > gas_tank.last_reset_epoch {
+        gas_tank.daily_spent = 0;
+        gas_tank.last_reset_epoch = clock.epoch;
+    }
+
+    // Caps what any relayer can pull out of the tank per epoch
+    gas_tank.check_and_spend(fee)?;
+
+    // The tank has to stay rent exempt after paying out
+    let tank_info = gas_tank.to_account_info();
+    require!(spendable_lamports(&tank_info)? >= fee, ErrorCode::InsufficientGasTank);
+    move_lamports(&tank_info, relayer, fee)?;
+
+    msg!("Relayer reimbursed: {} lamports", fee);
+    Ok(fee)
+}
+
+/// Appends an execution to the vault's spend ledger. Once a ledger is registered on the
+/// vault it has to be supplied, so an execution can't skip the audit trail.
+pub fn record_spend(vault: &Vault, spend_ledger: Option<&AccountLoader<SpendLedger>>, entry: SpendEntry) -> Result<()> {
+    let Some(registered) = vault.spend_ledger else {
+        return Ok(());
+    };
+    let spend_ledger = spend_ledger.ok_or(ErrorCode::MissingSpendLedger)?;
+    require_keys_eq!(spend_ledger.key(), registered, ErrorCode::InvalidSpendLedger);
+
+    spend_ledger.load_mut()?.push(entry);
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use crate::{events::{SpendLedgerClosed, SpendLedgerInitialized}, state::{SpendLedger, Vault}};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct InitSpendLedger<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = guardian,
+        seeds = [b"spend_ledger", vault.key().as_ref()],
+        bump,
+        space = SpendLedger::SPACE,
+    )]
+    pub spend_ledger: AccountLoader<'info, SpendLedger>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CloseSpendLedger<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"spend_ledger", vault.key().as_ref()],
+        bump,
+        close = guardian,
+    )]
+    pub spend_ledger: AccountLoader<'info, SpendLedger>,
+}
+
+pub fn init_spend_ledger(ctx: Context<InitSpendLedger>, _nonce: u64)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    let mut ledger = ctx.accounts.spend_ledger.load_init()?;
+    ledger.vault = ctx.accounts.vault.key();
+    drop(ledger);
+
+    // From here on every execution has to record into it
+    ctx.accounts.vault.spend_ledger = Some(ctx.accounts.spend_ledger.key());
+
+    emit_cpi!(SpendLedgerInitialized { vault: ctx.accounts.vault.key() });
+
+    msg!("Spend ledger initialized");
+    Ok(())
+}
+
+pub fn close_spend_ledger(ctx: Context<CloseSpendLedger>, _nonce: u64)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+    ctx.accounts.vault.spend_ledger = None;
+
+    emit_cpi!(SpendLedgerClosed { vault: ctx.accounts.vault.key() });
+
+    msg!("Spend ledger closed, rent returned to guardian");
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode,
+    events::{StreamCancelled, StreamCreated, StreamWithdrawn},
+    state::{spend_ledger::ROUTE_KIND_STREAM, OracleKind, PriceSource, SpendEntry, SpendLedger, Stream, Vault},
+};
+use super::{available_lamports, is_rent_exempt_after, record_spend, release_committed_lamports, require_rent_exempt_after};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_stream: u64)]
+pub struct CreateStream<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = guardian,
+        seeds = [b"stream", vault.key().as_ref(), &nonce_stream.to_le_bytes()],
+        bump,
+        space = Stream::DISCRIMINATOR.len() + Stream::INIT_SPACE,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_stream: u64)]
+pub struct WithdrawStream<'info>{
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    /// CHECK: Validated via vault PDA seeds (vault is derived from guardian's key)
+    pub guardian: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key.as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"stream", vault.key().as_ref(), &nonce_stream.to_le_bytes()],
+        bump = stream.bump,
+        has_one = vault,
+        has_one = recipient,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    /// Audit ring buffer of recent executions, required once registered on the vault
+    #[account(
+        mut,
+        address = vault.spend_ledger.unwrap_or_default() @ErrorCode::InvalidSpendLedger,
+    )]
+    pub spend_ledger: Option<AccountLoader<'info, SpendLedger>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_stream: u64)]
+pub struct CancelStream<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"stream", vault.key().as_ref(), &nonce_stream.to_le_bytes()],
+        bump = stream.bump,
+        has_one = vault,
+        has_one = recipient,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    /// CHECK: Receives what has vested, checked against the stream
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// Audit ring buffer of recent executions, required once registered on the vault
+    #[account(
+        mut,
+        address = vault.spend_ledger.unwrap_or_default() @ErrorCode::InvalidSpendLedger,
+    )]
+    pub spend_ledger: Option<AccountLoader<'info, SpendLedger>>,
+}
+
+// The full stream amount is committed up front, so it has to be available in the vault now
+pub fn create_stream(
+    ctx: Context<CreateStream>,
+    _nonce_vault: u64,
+    nonce_stream: u64,
+    recipient: Pubkey,
+    rate_per_second: u64,
+    start_time: i64,
+    end_time: i64,
+)->Result<()>{
+    let clock = Clock::get()?;
+
+    // A start in the past would vest instantly, streams start now at the earliest
+    let start_time = start_time.max(clock.unix_timestamp);
+    require!(rate_per_second > 0 && end_time > start_time, ErrorCode::InvalidStream);
+    require_keys_neq!(recipient, ctx.accounts.vault.key(), ErrorCode::InvalidDestination);
+
+    ctx.accounts.stream.set_inner(Stream {
+        vault: ctx.accounts.vault.key(),
+        recipient,
+        rate_per_second,
+        start_time,
+        end_time,
+        withdrawn: 0,
+        nonce: nonce_stream,
+        bump: ctx.bumps.stream,
+    });
+
+    let total = ctx.accounts.stream.total()?;
+    require!(total <= available_lamports(&ctx.accounts.vault)?, ErrorCode::InsufficientFunds);
+
+    let vault = &mut ctx.accounts.vault;
+    vault.committed_lamports = vault.committed_lamports.checked_add(total).ok_or(ErrorCode::Overflow)?;
+    vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    emit_cpi!(StreamCreated {
+        vault: ctx.accounts.vault.key(),
+        stream: ctx.accounts.stream.key(),
+        recipient,
+        rate_per_second,
+        start_time,
+        end_time,
+        total,
+    });
+
+    msg!("Stream created: {} lamports to {}", total, recipient);
+    Ok(())
+}
+
+// Owed funds, so this works on a frozen vault too
+pub fn withdraw_stream(ctx: Context<WithdrawStream>, _nonce_vault: u64, _nonce_stream: u64)->Result<()>{
+    let clock = Clock::get()?;
+
+    let amount = ctx.accounts.stream.withdrawable_at(clock.unix_timestamp)?;
+    require!(amount > 0, ErrorCode::NothingToWithdraw);
+    require_rent_exempt_after(&ctx.accounts.recipient.to_account_info(), amount)?;
+
+    release_committed_lamports(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.recipient.to_account_info(),
+        amount,
+    )?;
+
+    let stream = &mut ctx.accounts.stream;
+    stream.withdrawn = stream.withdrawn.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+    record_stream_payout(
+        &ctx.accounts.vault,
+        ctx.accounts.spend_ledger.as_ref(),
+        ctx.accounts.stream.key(),
+        ctx.accounts.recipient.key(),
+        &clock,
+        amount,
+    )?;
+
+    emit_cpi!(StreamWithdrawn {
+        vault: ctx.accounts.vault.key(),
+        stream: ctx.accounts.stream.key(),
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+        withdrawn: ctx.accounts.stream.withdrawn,
+    });
+
+    msg!("Stream withdrawal: {} lamports", amount);
+    Ok(())
+}
+
+// Stops vesting and releases the unvested rest back to the vault. What has vested is paid out
+// and the stream closed, unless the payout would leave the recipient below rent exemption: then
+// it stays committed and the stream is kept, claim-only, for the recipient to withdraw later.
+pub fn cancel_stream(ctx: Context<CancelStream>, _nonce_vault: u64, _nonce_stream: u64)->Result<()>{
+    let clock = Clock::get()?;
+    let stream = &mut ctx.accounts.stream;
+
+    let vested = stream.withdrawable_at(clock.unix_timestamp)?;
+    let returned = stream
+        .total()?
+        .checked_sub(stream.withdrawn)
+        .and_then(|remaining| remaining.checked_sub(vested))
+        .ok_or(ErrorCode::Overflow)?;
+    stream.end_time = clock.unix_timestamp.clamp(stream.start_time, stream.end_time);
+
+    let recipient = ctx.accounts.recipient.to_account_info();
+    let (paid_out, claimable) = if vested > 0 && is_rent_exempt_after(&recipient, vested)? {
+        (vested, 0)
+    } else {
+        (0, vested)
+    };
+
+    if paid_out > 0 {
+        release_committed_lamports(
+            &mut ctx.accounts.vault,
+            &ctx.accounts.recipient.to_account_info(),
+            paid_out,
+        )?;
+        record_stream_payout(
+            &ctx.accounts.vault,
+            ctx.accounts.spend_ledger.as_ref(),
+            ctx.accounts.stream.key(),
+            ctx.accounts.recipient.key(),
+            &clock,
+            paid_out,
+        )?;
+    }
+
+    let vault = &mut ctx.accounts.vault;
+    vault.committed_lamports = vault.committed_lamports.checked_sub(returned).ok_or(ErrorCode::Overflow)?;
+    vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    if claimable == 0 {
+        ctx.accounts.stream.close(ctx.accounts.guardian.to_account_info())?;
+    }
+
+    emit_cpi!(StreamCancelled {
+        vault: ctx.accounts.vault.key(),
+        stream: ctx.accounts.stream.key(),
+        paid_out,
+        returned,
+        claimable,
+    });
+
+    msg!("Stream cancelled: {} paid out, {} returned, {} left to claim", paid_out, returned, claimable);
+    Ok(())
+}
+
+fn record_stream_payout(
+    vault: &Vault,
+    spend_ledger: Option<&AccountLoader<SpendLedger>>,
+    stream: Pubkey,
+    recipient: Pubkey,
+    clock: &Clock,
+    amount: u64,
+)->Result<()>{
+    record_spend(
+        vault,
+        spend_ledger,
+        SpendEntry {
+            slot: clock.slot,
+            amount,
+            usd_value: 0,
+            delegate: stream,
+            mint: Pubkey::default(),
+            destination: recipient,
+            route_kind: ROUTE_KIND_STREAM,
+            price_source: PriceSource::Spot as u8,
+            oracle_kind: OracleKind::Unpriced as u8,
+            _padding: [0; 5],
+        },
+    )
+}
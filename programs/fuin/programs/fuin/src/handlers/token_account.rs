@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::ErrorCode,
+    events::{VaultTokenAccountClosed, VaultTokenAccountCreated},
+    state::{Vault, VaultTokenAccount},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CreateVaultTokenAccount<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault's associated token account, an existing one is adopted into the registry
+    #[account(
+        init_if_needed,
+        payer = guardian,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CloseVaultTokenAccount<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn create_vault_token_account(ctx: Context<CreateVaultTokenAccount>, _nonce: u64)->Result<()>{
+    let entry = VaultTokenAccount {
+        mint: ctx.accounts.mint.key(),
+        address: ctx.accounts.vault_token_account.key(),
+        token_program: ctx.accounts.token_program.key(),
+    };
+    require!(
+        ctx.accounts.vault.register_token_account(entry),
+        ErrorCode::TokenAccountRegistryFull
+    );
+
+    ctx.accounts.vault.recovery.last_guardian_activity = Clock::get()?.unix_timestamp;
+
+    emit_cpi!(VaultTokenAccountCreated {
+        vault: ctx.accounts.vault.key(),
+        mint: ctx.accounts.mint.key(),
+        token_account: ctx.accounts.vault_token_account.key(),
+        token_program: ctx.accounts.token_program.key(),
+    });
+
+    msg!("Vault token account {} ready", ctx.accounts.vault_token_account.key());
+    Ok(())
+}
+
+// Only empty accounts can be closed, the guardian withdraws the balance first.
+// Rent goes back to the guardian.
+pub fn close_vault_token_account(ctx: Context<CloseVaultTokenAccount>, _nonce: u64)->Result<()>{
+    require!(ctx.accounts.vault_token_account.amount == 0, ErrorCode::TokenAccountNotEmpty);
+
+    let vault = &ctx.accounts.vault;
+    let seeds: &[&[&[u8]]] = &[&[
+        b"vault",
+        vault.guardian.as_ref(),
+        &vault.nonce.to_le_bytes(),
+        &[vault.bump],
+    ]];
+
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.vault_token_account.to_account_info(),
+            destination: ctx.accounts.guardian.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        seeds,
+    ))?;
+
+    let mint = ctx.accounts.mint.key();
+    let vault = &mut ctx.accounts.vault;
+    vault.token_accounts.retain(|t| t.mint != mint);
+    vault.recovery.last_guardian_activity = Clock::get()?.unix_timestamp;
+
+    emit_cpi!(VaultTokenAccountClosed {
+        vault: ctx.accounts.vault.key(),
+        mint,
+        token_account: ctx.accounts.vault_token_account.key(),
+    });
+
+    msg!("Vault token account {} closed", ctx.accounts.vault_token_account.key());
+    Ok(())
+}
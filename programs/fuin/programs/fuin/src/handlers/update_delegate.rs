@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, events::DelegateUpdated, state::{Delegate, Vault}};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce_vault: u64, nonce_delegate: u64)]
+pub struct UpdateDelegate<'info>{
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce_vault.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"delegate",
+            vault.key().as_ref(),
+            &nonce_delegate.to_le_bytes(),
+        ],
+        bump = delegate.bump,
+        has_one = vault,
+    )]
+    pub delegate: Account<'info, Delegate>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_delegate(
+    ctx: Context<UpdateDelegate>,
+    _nonce_vault: u64,
+    _nonce_delegate: u64,
+    new_permissions: Option<u16>,
+    new_daily_limit: Option<u64>,
+    new_max_uses: Option<u32>,
+    new_validity_in_secs: Option<i64>,
+    new_allow_key_rotation: Option<bool>,
+    reset_uses: bool,
+)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    let delegate = &mut ctx.accounts.delegate;
+
+    // Revocation is final, renewing a revoked delegate would silently undo it
+    require!(!delegate.is_revoked(), ErrorCode::DelegateRevoked);
+
+    if let Some(permissions) = new_permissions {
+        delegate.permissions = permissions;
+        msg!("Delegate: Permissions updated to {}", permissions);
+    }
+
+    if let Some(limit) = new_daily_limit {
+        delegate.daily_limit = limit;
+        msg!("Delegate: Daily limit updated to {}", limit);
+    }
+
+    if let Some(max_uses) = new_max_uses {
+        delegate.max_uses = max_uses;
+        msg!("Delegate: Max uses updated to {}", max_uses);
+    }
+
+    if let Some(validity) = new_validity_in_secs {
+        delegate.expiry = clock.unix_timestamp
+            .checked_add(validity)
+            .ok_or(ErrorCode::Overflow)?;
+        msg!("Delegate: Expiry extended to {}", delegate.expiry);
+    }
+
+    if let Some(allow) = new_allow_key_rotation {
+        delegate.allow_key_rotation = allow;
+        msg!("Delegate: Key rotation allowed = {}", allow);
+    }
+
+    if reset_uses {
+        delegate.uses = 0;
+        msg!("Delegate: Uses reset");
+    }
+
+    emit_cpi!(DelegateUpdated {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.key(),
+        permissions: ctx.accounts.delegate.permissions,
+        daily_limit: ctx.accounts.delegate.daily_limit,
+        max_uses: ctx.accounts.delegate.max_uses,
+        uses: ctx.accounts.delegate.uses,
+        expiry: ctx.accounts.delegate.expiry,
+        allow_key_rotation: ctx.accounts.delegate.allow_key_rotation,
+    });
+
+    Ok(())
+}
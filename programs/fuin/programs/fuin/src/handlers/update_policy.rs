@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::events::PolicyUpdated;
+use crate::state::{FeedConfig, MintOracle, PeggedMint, Vault};
+use crate::token_extensions::is_known_extension;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct UpdatePolicy<'info>{
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn set_relayer_policy(ctx: Context<UpdatePolicy>, _nonce: u64, allow_list: Vec<Pubkey>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    require!(allow_list.len() <= 8, ErrorCode::WhitelistFull);
+    vault.policies.relayers.allow_list = allow_list;
+    msg!("Vault: Relayer allow list updated");
+
+    emit_cpi!(PolicyUpdated { vault: ctx.accounts.vault.key() });
+
+    Ok(())
+}
+
+pub fn set_oracle_policy(ctx: Context<UpdatePolicy>, _nonce: u64, feeds: Vec<FeedConfig>, mints: Vec<MintOracle>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    require!(feeds.len() <= 8, ErrorCode::WhitelistFull);
+    require!(mints.len() <= 8, ErrorCode::WhitelistFull);
+
+    // Instructions carry one account per oracle kind, so a fallback must use the other kind
+    for (i, mint_oracle) in mints.iter().enumerate() {
+        require!(mint_oracle.primary.kind.is_price_source(), ErrorCode::InvalidOracleConfig);
+        if let Some(fallback) = &mint_oracle.fallback {
+            require!(
+                fallback.kind != mint_oracle.primary.kind && fallback.kind.is_price_source(),
+                ErrorCode::InvalidOracleConfig
+            );
+        }
+        require!(
+            !mints[..i].iter().any(|other| other.mint == mint_oracle.mint),
+            ErrorCode::InvalidOracleConfig
+        );
+    }
+
+    vault.policies.oracle.feeds = feeds;
+    vault.policies.oracle.mints = mints;
+    msg!("Vault: Oracle policy updated");
+
+    emit_cpi!(PolicyUpdated { vault: ctx.accounts.vault.key() });
+
+    Ok(())
+}
+
+pub fn set_pegged_mints(ctx: Context<UpdatePolicy>, _nonce: u64, pegged: Vec<PeggedMint>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    require!(pegged.len() <= 8, ErrorCode::WhitelistFull);
+    for (i, entry) in pegged.iter().enumerate() {
+        require!(entry.usd_rate > 0, ErrorCode::InvalidOracleConfig);
+        require!(
+            !pegged[..i].iter().any(|other| other.mint == entry.mint),
+            ErrorCode::InvalidOracleConfig
+        );
+    }
+
+    vault.policies.oracle.pegged = pegged;
+    msg!("Vault: Pegged mints updated");
+
+    emit_cpi!(PolicyUpdated { vault: ctx.accounts.vault.key() });
+
+    Ok(())
+}
+
+pub fn set_blocked_extensions(ctx: Context<UpdatePolicy>, _nonce: u64, blocked_extensions: Vec<u16>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    require!(blocked_extensions.len() <= 8, ErrorCode::WhitelistFull);
+    require!(
+        blocked_extensions.iter().all(|extension| is_known_extension(*extension)),
+        ErrorCode::InvalidMintPolicy
+    );
+
+    vault.policies.mints.blocked_extensions = blocked_extensions;
+    msg!("Vault: Blocked mint extensions updated");
+
+    emit_cpi!(PolicyUpdated { vault: ctx.accounts.vault.key() });
+
+    Ok(())
+}
+
+pub fn set_mint_policy(ctx: Context<UpdatePolicy>, _nonce: u64, allow_list: Vec<Pubkey>, deny_list: Vec<Pubkey>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    require!(allow_list.len() <= 16, ErrorCode::WhitelistFull);
+    require!(deny_list.len() <= 8, ErrorCode::WhitelistFull);
+
+    vault.policies.mints.allow_list = allow_list;
+    vault.policies.mints.deny_list = deny_list;
+    msg!("Vault: Mint policy updated");
+
+    emit_cpi!(PolicyUpdated { vault: ctx.accounts.vault.key() });
+
+    Ok(())
+}
+
+pub fn set_risk_policy(
+    ctx: Context<UpdatePolicy>,
+    _nonce: u64,
+    max_slippage_bps: u16,
+    require_cosign_above: u64,
+    require_memo_above: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    vault.policies.risk.max_slippage_bps = max_slippage_bps;
+    vault.policies.risk.require_cosign_above = require_cosign_above;
+    vault.policies.risk.require_memo_above = require_memo_above;
+    msg!("Vault: Risk policy updated");
+
+    emit_cpi!(PolicyUpdated { vault: ctx.accounts.vault.key() });
+
+    Ok(())
+}
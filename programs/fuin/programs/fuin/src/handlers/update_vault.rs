@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 
 use crate::error::ErrorCode;
+use crate::events::PolicyUpdated;
 use crate::state::Vault;
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(nonce:u64)]
 pub struct UpdateVault<'info>{
@@ -18,6 +20,7 @@ pub struct UpdateVault<'info>{
     pub vault : Account<'info, Vault>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_vault(
     ctx: Context<UpdateVault>,
     _nonce: u64,
@@ -25,6 +28,9 @@ pub fn update_vault(
     new_per_tx_cap: Option<u64>,
     new_allow_list: Option<Vec<Pubkey>>,
     new_deny_list: Option<Vec<Pubkey>>,
+    new_uses_per_batch: Option<u32>,
+    new_daily_usd_cap: Option<u64>,
+    new_per_tx_usd_cap: Option<u64>,
 ) -> Result<()> {
     let clock = Clock::get()?;
     let vault = &mut ctx.accounts.vault;
@@ -52,5 +58,22 @@ pub fn update_vault(
         msg!("Vault: Deny list updated");
     }
 
+    if let Some(uses) = new_uses_per_batch {
+        vault.policies.spending.uses_per_batch = uses;
+        msg!("Vault: Uses per batch updated to {}", uses);
+    }
+
+    if let Some(cap) = new_daily_usd_cap {
+        vault.policies.spending.daily_usd_cap = cap;
+        msg!("Vault: Daily USD cap updated to {}", cap);
+    }
+
+    if let Some(cap) = new_per_tx_usd_cap {
+        vault.policies.spending.per_tx_usd_cap = cap;
+        msg!("Vault: Per-tx USD cap updated to {}", cap);
+    }
+
+    emit_cpi!(PolicyUpdated { vault: ctx.accounts.vault.key() });
+
     Ok(())
 }
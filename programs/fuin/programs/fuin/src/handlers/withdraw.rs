@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
 
-use crate::state::Vault;
+use crate::{events::Withdrawn, state::Vault};
+use super::transfer_lamports_from_vault;
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(nonce: u64)]
 pub struct Withdraw<'info>{
@@ -25,11 +27,19 @@ pub fn withdraw(ctx:Context<Withdraw>, _nonce: u64, amount: u64)->Result<()>{
     let vault = &mut ctx.accounts.vault;
     vault.recovery.last_guardian_activity = clock.unix_timestamp;
 
-    let vault_info = vault.to_account_info();
-    let recipient_info = ctx.accounts.guardian.to_account_info();
+    // Only lamports above the vault's rent-exempt minimum and not owed to streams can be withdrawn
+    transfer_lamports_from_vault(
+        vault,
+        &ctx.accounts.guardian.to_account_info(),
+        amount,
+    )?;
+
+    emit_cpi!(Withdrawn {
+        vault: ctx.accounts.vault.key(),
+        guardian: ctx.accounts.guardian.key(),
+        amount,
+    });
 
-    **vault_info.try_borrow_mut_lamports()? -= amount;
-    **recipient_info.try_borrow_mut_lamports()? += amount;
     msg!("Emergency Withdraw: {} lamports recovered", amount);
 
     Ok(())
@@ -0,0 +1,151 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{error::ErrorCode, events::SplWithdrawn, state::Vault};
+use super::transfer_spl_from_vault;
+
+// Token accounts per batch entry: vault source, mint, destination
+const BATCH_ENTRY_ACCOUNTS: usize = 3;
+pub const MAX_WITHDRAW_BATCH: usize = 8;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct WithdrawSpl<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.mint == mint.key() @ErrorCode::MintMismatch,
+        constraint = vault_token_account.owner == vault.key() @ErrorCode::VaultOwnerMismatch,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == mint.key() @ErrorCode::MintMismatch,
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Remaining accounts hold one (vault token account, mint, destination token account)
+/// triple per entry in `amounts`, all owned by `token_program`. Transfer hook mints
+/// aren't supported here, use `withdraw_spl`.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct WithdrawSplBatch<'info>{
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", guardian.key().as_ref(), &nonce.to_le_bytes()],
+        bump = vault.bump,
+        has_one = guardian,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Like `withdraw`, this is the guardian's escape hatch: no delegate limits apply and it
+// works on a frozen vault. It counts as guardian activity for the recovery timer.
+// Remaining accounts are forwarded as transfer hook extra accounts.
+pub fn withdraw_spl<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawSpl<'info>>, _nonce: u64, amount: u64)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    transfer_spl_from_vault(
+        &ctx.accounts.vault,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.vault_token_account.to_account_info(),
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.destination_token_account.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    emit_cpi!(SplWithdrawn {
+        vault: ctx.accounts.vault.key(),
+        guardian: ctx.accounts.guardian.key(),
+        mint: ctx.accounts.mint.key(),
+        source: ctx.accounts.vault_token_account.key(),
+        destination: ctx.accounts.destination_token_account.key(),
+        amount,
+    });
+
+    msg!("Emergency Withdraw: {} tokens recovered", amount);
+    Ok(())
+}
+
+pub fn withdraw_spl_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawSplBatch<'info>>,
+    _nonce: u64,
+    amounts: Vec<u64>,
+)->Result<()>{
+    let clock = Clock::get()?;
+    ctx.accounts.vault.recovery.last_guardian_activity = clock.unix_timestamp;
+
+    require!(
+        !amounts.is_empty() && amounts.len() <= MAX_WITHDRAW_BATCH,
+        ErrorCode::InvalidBatch
+    );
+    require!(
+        ctx.remaining_accounts.len() == amounts.len() * BATCH_ENTRY_ACCOUNTS,
+        ErrorCode::InvalidBatch
+    );
+
+    let vault_key = ctx.accounts.vault.key();
+    let token_program = ctx.accounts.token_program.to_account_info();
+
+    for (accounts, &amount) in ctx.remaining_accounts.chunks_exact(BATCH_ENTRY_ACCOUNTS).zip(amounts.iter()) {
+        let (source_info, mint_info, destination_info) = (&accounts[0], &accounts[1], &accounts[2]);
+
+        // Remaining accounts skip Anchor's constraints, so check what `WithdrawSpl` would
+        let source = InterfaceAccount::<TokenAccount>::try_from(source_info)?;
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        let destination = InterfaceAccount::<TokenAccount>::try_from(destination_info)?;
+        require_keys_eq!(source.mint, mint.key(), ErrorCode::MintMismatch);
+        require_keys_eq!(destination.mint, mint.key(), ErrorCode::MintMismatch);
+        require_keys_eq!(source.owner, vault_key, ErrorCode::VaultOwnerMismatch);
+        require_keys_eq!(*source_info.owner, token_program.key(), ErrorCode::InvalidTokenProgram);
+
+        transfer_spl_from_vault(
+            &ctx.accounts.vault,
+            &token_program,
+            source_info,
+            mint_info,
+            destination_info,
+            &[],
+            amount,
+            mint.decimals,
+        )?;
+
+        emit_cpi!(SplWithdrawn {
+            vault: vault_key,
+            guardian: ctx.accounts.guardian.key(),
+            mint: mint.key(),
+            source: source.key(),
+            destination: destination.key(),
+            amount,
+        });
+    }
+
+    msg!("Emergency Withdraw: {} token accounts recovered", amounts.len());
+    Ok(())
+}
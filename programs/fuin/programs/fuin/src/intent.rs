@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use solana_instructions_sysvar::get_instruction_relative;
+use solana_sdk_ids::ed25519_program;
+
+use crate::{error::ErrorCode, state::Route};
+
+// Prefix on every signed intent so the signature can't be reused as some other message
+pub const INTENT_DOMAIN: &[u8] = b"fuin:intent:v1";
+
+// Ed25519 program instruction layout: [num_signatures u8, padding u8, offsets (7 x u16)]
+const ED25519_HEADER_LEN: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+// Offsets pointing at this index refer to the Ed25519 instruction's own data
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Off-chain message a delegate signs instead of signing the transaction itself
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Intent {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub route: Route,
+    pub nonce: u64,
+    // unix timestamp after which the intent is no longer valid
+    pub deadline: i64,
+}
+
+impl Intent {
+    /// Bytes the delegate signs: domain prefix followed by the borsh encoded intent
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = INTENT_DOMAIN.to_vec();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+/// Checks that the instruction right before this one is an Ed25519 program
+/// verification of `message` signed by `signer`. The Ed25519 program has already
+/// rejected the transaction if the signature itself was invalid, so all that is
+/// left is making sure it verified the key and bytes we expect.
+pub fn verify_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let ix = get_instruction_relative(-1, instructions_sysvar)
+        .map_err(|_| ErrorCode::MissingSignatureVerification)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, ErrorCode::MissingSignatureVerification);
+    require!(ix.accounts.is_empty(), ErrorCode::InvalidSignatureVerification);
+
+    let data = &ix.data;
+    require!(data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN, ErrorCode::InvalidSignatureVerification);
+    require!(data[0] == 1, ErrorCode::InvalidSignatureVerification);
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = ED25519_HEADER_LEN;
+    let signature_ix_index = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4) as usize;
+    let pubkey_ix_index = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_size = read_u16(offsets + 10) as usize;
+    let message_ix_index = read_u16(offsets + 12);
+
+    // Everything has to live inside the Ed25519 instruction, otherwise the
+    // offsets could point at bytes we never look at
+    require!(
+        signature_ix_index == CURRENT_INSTRUCTION
+            && pubkey_ix_index == CURRENT_INSTRUCTION
+            && message_ix_index == CURRENT_INSTRUCTION,
+        ErrorCode::InvalidSignatureVerification
+    );
+
+    let pubkey = data
+        .get(pubkey_offset..pubkey_offset + PUBKEY_LEN)
+        .ok_or(ErrorCode::InvalidSignatureVerification)?;
+    require!(pubkey == signer.as_ref(), ErrorCode::InvalidSignatureVerification);
+
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCode::InvalidSignatureVerification)?;
+    require!(signed_message == message, ErrorCode::InvalidSignatureVerification);
+
+    Ok(())
+}
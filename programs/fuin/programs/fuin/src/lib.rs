@@ -8,8 +8,15 @@ pub mod state;
 pub mod handlers;
 pub mod error;
 pub mod pricing;
+pub mod token_extensions;
+pub mod intent;
+pub mod events;
 
+// Handler fns clash with the #[program] instruction fns of the same name, those stay reachable as `handlers::*`
+#[allow(ambiguous_glob_reexports)]
 pub use handlers::*;
+use intent::Intent;
+use state::{FeedConfig, MintOracle, PeggedMint};
 
 #[program]
 pub mod fuin {
@@ -19,6 +26,7 @@ pub mod fuin {
         handlers::init_vault(ctx, nonce, daily_cap, per_tx_cap, allowed_programs)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn issue_delegate(
         ctx: Context<IssueDelegate>,
         vault_nonce: u64,
@@ -32,16 +40,34 @@ pub mod fuin {
         handlers::issue_delegate(ctx, vault_nonce, delegate_nonce, delegate_key, permissions, daily_limit, max_uses, validity_in_secs)
     }
 
-    pub fn execute_transfer<'info>(ctx: Context<ExecuteTransfer>, nonce_vault: u64, nonce_delegate: u64, amount: u64)->Result<()>{
-        handlers::execute_transfer(ctx, nonce_vault, nonce_delegate, amount)
+    pub fn execute_transfer(ctx: Context<ExecuteTransfer>, nonce_vault: u64, nonce_delegate: u64, amount: u64, memo: Option<String>)->Result<()>{
+        handlers::execute_transfer(ctx, nonce_vault, nonce_delegate, amount, memo)
     }
 
-    pub fn execute_spl_transfer(ctx: Context<ExecuteSplTransfer>, nonce_vault: u64, nonce_delegate: u64, amount: u64, feed_id: String)->Result<()>{
-        handlers::execute_spl_transfer(ctx, nonce_vault, nonce_delegate, amount, feed_id)
+    pub fn execute_spl_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSplTransfer<'info>>,
+        nonce_vault: u64,
+        nonce_delegate: u64,
+        amount: u64,
+        feed_id: Option<String>,
+        memo: Option<String>,
+    )->Result<()>{
+        handlers::execute_spl_transfer(ctx, nonce_vault, nonce_delegate, amount, feed_id, memo)
     }
 
-    pub fn update_vault(ctx: Context<UpdateVault>, nonce: u64, new_daily_cap: Option<u64>, new_per_tx_cap: Option<u64>, new_allow_list: Option<Vec<Pubkey>>, new_deny_list: Option<Vec<Pubkey>>)->Result<()>{
-        handlers::update_vault(ctx, nonce, new_daily_cap, new_per_tx_cap, new_allow_list, new_deny_list)
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_vault(
+        ctx: Context<UpdateVault>,
+        nonce: u64,
+        new_daily_cap: Option<u64>,
+        new_per_tx_cap: Option<u64>,
+        new_allow_list: Option<Vec<Pubkey>>,
+        new_deny_list: Option<Vec<Pubkey>>,
+        new_uses_per_batch: Option<u32>,
+        new_daily_usd_cap: Option<u64>,
+        new_per_tx_usd_cap: Option<u64>,
+    )->Result<()>{
+        handlers::update_vault(ctx, nonce, new_daily_cap, new_per_tx_cap, new_allow_list, new_deny_list, new_uses_per_batch, new_daily_usd_cap, new_per_tx_usd_cap)
     }
 
     pub fn delegate_control(ctx: Context<DelegateControl>, nonce_vault: u64, nonce_delegate: u64, status: u8)->Result<()>{
@@ -66,8 +92,201 @@ pub mod fuin {
         nonce_delegate: u64,
         amount_in: u64,
         min_amount_out: u64,
-        feed_id: String,
+        feed_id: Option<String>,
     ) -> Result<()> {
         handlers::execute_swap(ctx, nonce_vault, nonce_delegate, amount_in, min_amount_out, feed_id)
     }
+
+    pub fn close_delegate(ctx: Context<CloseDelegate>, nonce_vault: u64, nonce_delegate: u64) -> Result<()> {
+        handlers::close_delegate(ctx, nonce_vault, nonce_delegate)
+    }
+
+    pub fn crank_close_delegate(ctx: Context<CrankCloseDelegate>, nonce_vault: u64, nonce_delegate: u64) -> Result<()> {
+        handlers::crank_close_delegate(ctx, nonce_vault, nonce_delegate)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_delegate(
+        ctx: Context<UpdateDelegate>,
+        nonce_vault: u64,
+        nonce_delegate: u64,
+        new_permissions: Option<u16>,
+        new_daily_limit: Option<u64>,
+        new_max_uses: Option<u32>,
+        new_validity_in_secs: Option<i64>,
+        new_allow_key_rotation: Option<bool>,
+        reset_uses: bool,
+    ) -> Result<()> {
+        handlers::update_delegate(ctx, nonce_vault, nonce_delegate, new_permissions, new_daily_limit, new_max_uses, new_validity_in_secs, new_allow_key_rotation, reset_uses)
+    }
+
+    pub fn rotate_delegate_key(ctx: Context<RotateDelegateKey>, nonce_vault: u64, nonce_delegate: u64) -> Result<()> {
+        handlers::rotate_delegate_key(ctx, nonce_vault, nonce_delegate)
+    }
+
+    pub fn migrate_vault(ctx: Context<MigrateVault>, nonce: u64) -> Result<()> {
+        handlers::migrate_vault(ctx, nonce)
+    }
+
+    pub fn migrate_delegate(ctx: Context<MigrateDelegate>, nonce_vault: u64, nonce_delegate: u64) -> Result<()> {
+        handlers::migrate_delegate(ctx, nonce_vault, nonce_delegate)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn issue_subdelegate(
+        ctx: Context<IssueSubdelegate>,
+        nonce_vault: u64,
+        nonce_parent: u64,
+        nonce_child: u64,
+        delegate_key: Pubkey,
+        permissions: u16,
+        daily_limit: u64,
+        max_uses: u32,
+        validity_in_secs: i64,
+    ) -> Result<()> {
+        handlers::issue_subdelegate(ctx, nonce_vault, nonce_parent, nonce_child, delegate_key, permissions, daily_limit, max_uses, validity_in_secs)
+    }
+
+    pub fn execute_intent(ctx: Context<ExecuteIntent>, nonce_vault: u64, nonce_delegate: u64, intent: Intent) -> Result<()> {
+        handlers::execute_intent(ctx, nonce_vault, nonce_delegate, intent)
+    }
+
+    pub fn init_gas_tank(ctx: Context<InitGasTank>, nonce: u64, fee_per_call: u64, daily_cap: u64) -> Result<()> {
+        handlers::init_gas_tank(ctx, nonce, fee_per_call, daily_cap)
+    }
+
+    pub fn fund_gas_tank(ctx: Context<ManageGasTank>, nonce: u64, amount: u64) -> Result<()> {
+        handlers::fund_gas_tank(ctx, nonce, amount)
+    }
+
+    pub fn update_gas_tank(ctx: Context<ManageGasTank>, nonce: u64, new_fee_per_call: Option<u64>, new_daily_cap: Option<u64>) -> Result<()> {
+        handlers::update_gas_tank(ctx, nonce, new_fee_per_call, new_daily_cap)
+    }
+
+    pub fn close_gas_tank(ctx: Context<CloseGasTank>, nonce: u64) -> Result<()> {
+        handlers::close_gas_tank(ctx, nonce)
+    }
+
+    pub fn set_relayer_policy(ctx: Context<UpdatePolicy>, nonce: u64, allow_list: Vec<Pubkey>) -> Result<()> {
+        handlers::set_relayer_policy(ctx, nonce, allow_list)
+    }
+
+    pub fn init_spend_ledger(ctx: Context<InitSpendLedger>, nonce: u64) -> Result<()> {
+        handlers::init_spend_ledger(ctx, nonce)
+    }
+
+    pub fn close_spend_ledger(ctx: Context<CloseSpendLedger>, nonce: u64) -> Result<()> {
+        handlers::close_spend_ledger(ctx, nonce)
+    }
+
+    pub fn set_oracle_policy(ctx: Context<UpdatePolicy>, nonce: u64, feeds: Vec<FeedConfig>, mints: Vec<MintOracle>) -> Result<()> {
+        handlers::set_oracle_policy(ctx, nonce, feeds, mints)
+    }
+
+    pub fn set_pegged_mints(ctx: Context<UpdatePolicy>, nonce: u64, pegged: Vec<PeggedMint>) -> Result<()> {
+        handlers::set_pegged_mints(ctx, nonce, pegged)
+    }
+
+    pub fn withdraw_spl<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawSpl<'info>>, nonce: u64, amount: u64) -> Result<()> {
+        handlers::withdraw_spl(ctx, nonce, amount)
+    }
+
+    pub fn withdraw_spl_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawSplBatch<'info>>,
+        nonce: u64,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        handlers::withdraw_spl_batch(ctx, nonce, amounts)
+    }
+
+    pub fn deposit_sol(ctx: Context<DepositSol>, nonce: u64, amount: u64) -> Result<()> {
+        handlers::deposit_sol(ctx, nonce, amount)
+    }
+
+    pub fn deposit_spl<'info>(ctx: Context<'_, '_, '_, 'info, DepositSpl<'info>>, nonce: u64, amount: u64) -> Result<()> {
+        handlers::deposit_spl(ctx, nonce, amount)
+    }
+
+    pub fn create_vault_token_account(ctx: Context<CreateVaultTokenAccount>, nonce: u64) -> Result<()> {
+        handlers::create_vault_token_account(ctx, nonce)
+    }
+
+    pub fn close_vault_token_account(ctx: Context<CloseVaultTokenAccount>, nonce: u64) -> Result<()> {
+        handlers::close_vault_token_account(ctx, nonce)
+    }
+
+    pub fn set_blocked_extensions(ctx: Context<UpdatePolicy>, nonce: u64, blocked_extensions: Vec<u16>) -> Result<()> {
+        handlers::set_blocked_extensions(ctx, nonce, blocked_extensions)
+    }
+
+    pub fn set_mint_policy(ctx: Context<UpdatePolicy>, nonce: u64, allow_list: Vec<Pubkey>, deny_list: Vec<Pubkey>) -> Result<()> {
+        handlers::set_mint_policy(ctx, nonce, allow_list, deny_list)
+    }
+
+    pub fn set_risk_policy(
+        ctx: Context<UpdatePolicy>,
+        nonce: u64,
+        max_slippage_bps: u16,
+        require_cosign_above: u64,
+        require_memo_above: u64,
+    ) -> Result<()> {
+        handlers::set_risk_policy(ctx, nonce, max_slippage_bps, require_cosign_above, require_memo_above)
+    }
+
+    pub fn execute_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteBatch<'info>>,
+        nonce_vault: u64,
+        nonce_delegate: u64,
+        items: Vec<BatchTransfer>,
+    ) -> Result<()> {
+        handlers::execute_batch(ctx, nonce_vault, nonce_delegate, items)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_schedule(
+        ctx: Context<CreateSchedule>,
+        nonce_vault: u64,
+        nonce_schedule: u64,
+        recipient: Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+        interval_seconds: i64,
+        start_time: i64,
+        end_time: i64,
+        max_payments: u32,
+    ) -> Result<()> {
+        handlers::create_schedule(ctx, nonce_vault, nonce_schedule, recipient, mint, amount, interval_seconds, start_time, end_time, max_payments)
+    }
+
+    pub fn cancel_schedule(ctx: Context<CancelSchedule>, nonce_vault: u64, nonce_schedule: u64) -> Result<()> {
+        handlers::cancel_schedule(ctx, nonce_vault, nonce_schedule)
+    }
+
+    pub fn crank_schedule<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrankSchedule<'info>>,
+        nonce_vault: u64,
+        nonce_schedule: u64,
+    ) -> Result<()> {
+        handlers::crank_schedule(ctx, nonce_vault, nonce_schedule)
+    }
+
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        nonce_vault: u64,
+        nonce_stream: u64,
+        recipient: Pubkey,
+        rate_per_second: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        handlers::create_stream(ctx, nonce_vault, nonce_stream, recipient, rate_per_second, start_time, end_time)
+    }
+
+    pub fn withdraw_stream(ctx: Context<WithdrawStream>, nonce_vault: u64, nonce_stream: u64) -> Result<()> {
+        handlers::withdraw_stream(ctx, nonce_vault, nonce_stream)
+    }
+
+    pub fn cancel_stream(ctx: Context<CancelStream>, nonce_vault: u64, nonce_stream: u64) -> Result<()> {
+        handlers::cancel_stream(ctx, nonce_vault, nonce_stream)
+    }
 }
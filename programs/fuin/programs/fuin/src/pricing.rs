@@ -1,59 +1,1087 @@
 use anchor_lang::prelude::*;
-use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, get_feed_id_from_hex};
-use crate::error::ErrorCode;
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel, get_feed_id_from_hex};
+use switchboard_on_demand::{PullFeedAccountData, PRECISION as SWITCHBOARD_PRECISION, SWITCHBOARD_ON_DEMAND_PROGRAM_ID};
+use crate::{error::ErrorCode, state::{FeedConfig, OracleKind, OraclePolicy, OracleSource, PeggedMint, PriceSource}};
 
-// Price must be younger than 60 sec
+// Default max price age in seconds, for feeds the guardian hasn't configured
 pub const MAXIMUM_AGE: u64 = 60;
 // TODO: Add more Id's & store the constants at better place
 pub const PRICE_FEED_ID_SOL: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
 
+const BPS_DENOMINATOR: u128 = 10_000;
+// Values are reported in micro-USD
+const USD_DECIMALS: i64 = 6;
 
+/// USD value of a spend together with the oracle price it was derived from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UsdValuation {
+    // micro-USD
+    pub usd_value: u64,
+    pub source: PriceSource,
+    pub oracle: OracleKind,
+}
+
+/// Raw oracle quote: `price * 10^expo` USD per whole token, `conf` in the same units
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: i128,
+    pub conf: u128,
+    pub expo: i32,
+    pub publish_time: i64,
+    // price actually returned, oracles without an EMA always answer with spot
+    pub source: PriceSource,
+}
+
+/// A price feed spends can be valued with
+pub trait PriceOracle {
+    fn kind(&self) -> OracleKind;
+
+    /// Key the vault's `FeedConfig` entries are matched against
+    fn feed_key(&self) -> [u8; 32];
+
+    /// Unchecked quote for `source`, staleness and confidence are enforced by the caller
+    fn quote(&self, source: PriceSource) -> Result<OraclePrice>;
+}
+
+pub struct PythOracle<'a> {
+    pub price_update: &'a PriceUpdateV2,
+    pub feed_id: [u8; 32],
+}
+
+impl PriceOracle for PythOracle<'_> {
+    fn kind(&self) -> OracleKind {
+        OracleKind::Pyth
+    }
+
+    fn feed_key(&self) -> [u8; 32] {
+        self.feed_id
+    }
+
+    fn quote(&self, source: PriceSource) -> Result<OraclePrice> {
+        require!(
+            self.price_update.verification_level.gte(VerificationLevel::Full),
+            ErrorCode::UnverifiedPrice
+        );
+        // Also checks the feed id, the EMA comes from the same message
+        let spot = self.price_update.get_price_unchecked(&self.feed_id)?;
+        let message = &self.price_update.price_message;
+
+        let (price, conf, source) = match source {
+            PriceSource::Ema => (message.ema_price, message.ema_conf, PriceSource::Ema),
+            _ => (spot.price, spot.conf, PriceSource::Spot),
+        };
+        Ok(OraclePrice {
+            price: price as i128,
+            conf: conf as u128,
+            expo: spot.exponent,
+            publish_time: spot.publish_time,
+            source,
+        })
+    }
+}
+
+pub struct SwitchboardOracle<'a> {
+    pub key: Pubkey,
+    pub feed: &'a PullFeedAccountData,
+}
+
+impl PriceOracle for SwitchboardOracle<'_> {
+    fn kind(&self) -> OracleKind {
+        OracleKind::Switchboard
+    }
+
+    fn feed_key(&self) -> [u8; 32] {
+        self.key.to_bytes()
+    }
+
+    fn quote(&self, _source: PriceSource) -> Result<OraclePrice> {
+        let result = &self.feed.result;
+        // Slot 0 means the feed has never produced a result
+        require!(result.slot > 0, ErrorCode::InvalidOracleAccount);
+        let publish_time = *self
+            .feed
+            .submission_timestamps
+            .get(result.submission_idx as usize)
+            .ok_or(ErrorCode::InvalidOracleAccount)?;
+
+        // Median of the oracle submissions, std dev stands in for the confidence interval
+        Ok(OraclePrice {
+            price: result.value,
+            conf: result.std_dev.unsigned_abs(),
+            expo: -(SWITCHBOARD_PRECISION as i32),
+            publish_time,
+            source: PriceSource::Spot,
+        })
+    }
+}
+
+/// Oracle accounts passed to an instruction, either may be omitted
+pub struct OracleAccounts<'a, 'info> {
+    pub price_update: Option<&'a PriceUpdateV2>,
+    pub switchboard_feed: Option<&'a AccountInfo<'info>>,
+}
+
+/// Price that passed staleness and confidence checks, spends are valued at `value_price`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CheckedPrice {
+    pub price: u128,
+    pub value_price: u128,
+    pub expo: i32,
+    pub source: PriceSource,
+    pub oracle: OracleKind,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn calculate_usd_value(
-    price_update: &Account<PriceUpdateV2>,
-    feed_id_hex: &str,
+    oracles: &OracleAccounts,
+    mint: &Pubkey,
+    feed_id_hex: Option<&str>,
     amount: u64,
     decimals: u8,
-)->Result<u64>{
-    // Get Price 
-    let feed_id = get_feed_id_from_hex(feed_id_hex)?;
-    let price_data = price_update.get_price_no_older_than(
-        &Clock::get()?, 
-        MAXIMUM_AGE, 
-        &feed_id
-    )?;
+    oracle_policy: &OraclePolicy,
+    rounding: Rounding,
+)->Result<UsdValuation>{
+    // Pegged mints skip the oracle, except to feed the depeg guard when one is set
+    if let Some(pegged) = oracle_policy.pegged_mint(mint) {
+        if pegged.max_depeg_bps > 0 {
+            guard_peg(oracles, pegged, mint, feed_id_hex, oracle_policy, Clock::get()?.unix_timestamp)?;
+        }
+        return Ok(UsdValuation {
+            usd_value: pegged_usd_value(pegged, amount, decimals, rounding)?,
+            source: PriceSource::Spot,
+            oracle: OracleKind::Peg,
+        });
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let price = oracle_price(oracles, mint, feed_id_hex, oracle_policy, now)?;
+
+    let usd_value = usd_value_from_price(price.value_price, price.expo, amount, decimals, rounding)?;
+    Ok(UsdValuation {
+        usd_value,
+        source: price.source,
+        oracle: price.oracle,
+    })
+}
+
+/// Checked price for `mint`, from its primary oracle or the fallback when the primary is stale or too wide
+pub fn oracle_price(
+    oracles: &OracleAccounts,
+    mint: &Pubkey,
+    feed_id_hex: Option<&str>,
+    oracle_policy: &OraclePolicy,
+    now: i64,
+)->Result<CheckedPrice>{
+    match oracle_policy.mint_oracle(mint) {
+        Some(mint_oracle) => {
+            let primary = read_oracle(oracles, &mint_oracle.primary, oracle_policy, now);
+            let fallback = mint_oracle
+                .fallback
+                .as_ref()
+                .map(|fallback| read_oracle(oracles, fallback, oracle_policy, now));
+
+            match (primary, fallback) {
+                // Both usable, they have to agree before either is trusted
+                (Ok(primary), Some(Ok(fallback))) => {
+                    check_divergence(&primary, &fallback, mint_oracle.max_divergence_bps)?;
+                    Ok(primary)
+                }
+                // Leaving the fallback out would skip the divergence check, so it's required when one is set
+                (Ok(_), Some(Err(err)))
+                    if mint_oracle.max_divergence_bps > 0 && is_error(&err, ErrorCode::MissingOracleAccount) =>
+                {
+                    Err(err)
+                }
+                (Ok(primary), _) => Ok(primary),
+                // Only a supplied primary that is stale or too wide falls back, a missing one is an error
+                (Err(err), Some(Ok(fallback)))
+                    if is_error(&err, ErrorCode::StalePrice) || is_error(&err, ErrorCode::LowConfidencePrice) =>
+                {
+                    Ok(fallback)
+                }
+                // Neither usable, report why the primary failed
+                (Err(err), _) => Err(err),
+            }
+        }
+        None => {
+            // Mints without an oracle entry are valued with the caller's Pyth feed
+            let feed_id = get_feed_id_from_hex(feed_id_hex.ok_or(ErrorCode::MissingFeedId)?)?;
+            read_pyth(oracles, feed_id, oracle_policy, now)
+        }
+    }
+}
 
-    // Pyth price came with an exponent 
-    // We want to normalize everything to a standard USD precision (6 decimals)
-    let price = price_data.price; // i64
-    let price_expo = price_data.exponent; // i32
+fn is_error(err: &Error, code: ErrorCode) -> bool {
+    matches!(err, Error::AnchorError(e) if e.error_code_number == u32::from(code))
+}
 
-    require!(price > 0, ErrorCode::InvalidPrice);
+fn read_oracle(
+    oracles: &OracleAccounts,
+    source: &OracleSource,
+    oracle_policy: &OraclePolicy,
+    now: i64,
+)->Result<CheckedPrice>{
+    match source.kind {
+        OracleKind::Pyth => read_pyth(oracles, source.feed, oracle_policy, now),
+        OracleKind::Peg | OracleKind::Unpriced => err!(ErrorCode::InvalidOracleConfig),
+        OracleKind::Switchboard => {
+            let info = oracles
+                .switchboard_feed
+                .ok_or(ErrorCode::MissingOracleAccount)?;
+            require_keys_eq!(*info.key, Pubkey::new_from_array(source.feed), ErrorCode::InvalidOracleAccount);
+            require_keys_eq!(*info.owner, SWITCHBOARD_ON_DEMAND_PROGRAM_ID, ErrorCode::InvalidOracleAccount);
 
-    let u_price = price as u128;
-    let u_amount = amount as u128;
+            let data = info.try_borrow_data()?;
+            let feed = PullFeedAccountData::parse(data)
+                .map_err(|_| ErrorCode::InvalidOracleAccount)?;
+            let oracle = SwitchboardOracle { key: *info.key, feed: &feed };
+            checked_price(&oracle, oracle_policy, now)
+        }
+    }
+}
+
+fn read_pyth(
+    oracles: &OracleAccounts,
+    feed_id: [u8; 32],
+    oracle_policy: &OraclePolicy,
+    now: i64,
+)->Result<CheckedPrice>{
+    let price_update = oracles
+        .price_update
+        .ok_or(ErrorCode::MissingOracleAccount)?;
+    let oracle = PythOracle { price_update, feed_id };
+    checked_price(&oracle, oracle_policy, now)
+}
+
+/// Quotes `oracle` with the feed's configured price source and checks the result
+pub fn checked_price(
+    oracle: &impl PriceOracle,
+    oracle_policy: &OraclePolicy,
+    now: i64,
+)->Result<CheckedPrice>{
+    let feed_config = oracle_policy.feed_config(&oracle.feed_key());
+    match feed_config.map_or(PriceSource::Spot, |config| config.price_source) {
+        PriceSource::MaxOfSpotAndEma => {
+            let spot = checked_quote(oracle, PriceSource::Spot, feed_config, now)?;
+            let ema = checked_quote(oracle, PriceSource::Ema, feed_config, now)?;
+            // Report whichever price actually set the value
+            Ok(if ema.value_price > spot.value_price { ema } else { spot })
+        }
+        source => checked_quote(oracle, source, feed_config, now),
+    }
+}
 
-    let target_decimal: i32 = 6;
+fn checked_quote(
+    oracle: &impl PriceOracle,
+    source: PriceSource,
+    feed_config: Option<&FeedConfig>,
+    now: i64,
+)->Result<CheckedPrice>{
+    let quote = oracle.quote(source)?;
 
-    // Value = Price * Amount
-    // Value = p * 10^e * amount * 10^-d (as amounts will be passed in lamports so we are converting that back to sol)
-    // till now we got our answer in $USD, but we have to store that in Micro-USD so multiply by 10^6
+    // Staleness, checked here rather than by the SDKs so it gets its own error
+    let max_age = match feed_config {
+        Some(config) if config.max_age > 0 => config.max_age,
+        _ => MAXIMUM_AGE,
+    };
+    let max_age = i64::try_from(max_age).unwrap_or(i64::MAX);
+    require!(
+        quote.publish_time.saturating_add(max_age) >= now,
+        ErrorCode::StalePrice
+    );
 
-    let total_decimals = decimals as i32 + price_expo.abs();
-    let numerator = u_amount * u_price;
+    require!(quote.price > 0, ErrorCode::InvalidPrice);
+    let price = quote.price as u128;
 
-    if total_decimals > target_decimal {
-        // We need to divide to remove extra precision
-        let diff = total_decimals - target_decimal;
-        let divisor = 10u128.pow(diff as u32);
+    // Confidence interval has the same exponent as the price, so compare them directly
+    let max_conf_bps = feed_config.map_or(0, |config| config.max_conf_bps);
+    if max_conf_bps > 0 {
+        let max_conf = price
+            .checked_mul(max_conf_bps as u128)
+            .ok_or(ErrorCode::PricingOverflow)?;
+        let scaled_conf = quote
+            .conf
+            .checked_mul(BPS_DENOMINATOR)
+            .ok_or(ErrorCode::PricingOverflow)?;
+        require!(scaled_conf <= max_conf, ErrorCode::LowConfidencePrice);
+    }
 
-        Ok((numerator / divisor) as u64)
+    // Conservative valuation uses the top of the confidence interval
+    let value_price = if feed_config.is_some_and(|config| config.use_upper_bound) {
+        price
+            .checked_add(quote.conf)
+            .ok_or(ErrorCode::PricingOverflow)?
     } else {
-        // We need to multiply to add precision
-        let diff = target_decimal - total_decimals;
-        let multiplier = 10u128.pow(diff as u32);
+        price
+    };
+
+    Ok(CheckedPrice {
+        price,
+        value_price,
+        expo: quote.expo,
+        source: quote.source,
+        oracle: oracle.kind(),
+    })
+}
 
-        Ok((numerator * multiplier) as u64)
+/// Fails when the two prices differ by more than `max_divergence_bps` of the lower one (0 = unchecked)
+pub fn check_divergence(a: &CheckedPrice, b: &CheckedPrice, max_divergence_bps: u16) -> Result<()> {
+    if max_divergence_bps == 0 {
+        return Ok(());
     }
 
+    require!(within_bps(a, b, max_divergence_bps)?, ErrorCode::PriceDivergence);
+    Ok(())
+}
+
+/// Value of `amount` at the pegged mint's fixed rate
+pub fn pegged_usd_value(pegged: &PeggedMint, amount: u64, decimals: u8, rounding: Rounding) -> Result<u64> {
+    usd_value_from_price(pegged.usd_rate as u128, -(USD_DECIMALS as i32), amount, decimals, rounding)
+}
+
+/// Depeg guard, fails when the oracle price is more than `max_depeg_bps` away from the peg
+pub fn check_peg(pegged: &PeggedMint, price: &CheckedPrice) -> Result<()> {
+    let peg = CheckedPrice {
+        price: pegged.usd_rate as u128,
+        value_price: pegged.usd_rate as u128,
+        expo: -(USD_DECIMALS as i32),
+        source: PriceSource::Spot,
+        oracle: OracleKind::Peg,
+    };
+    require!(within_bps(&peg, price, pegged.max_depeg_bps)?, ErrorCode::MintDepegged);
+    Ok(())
+}
+
+/// Runs the depeg guard against the mint's oracle. The oracle accounts are required, leaving
+/// them out must not skip the guard.
+pub fn guard_peg(
+    oracles: &OracleAccounts,
+    pegged: &PeggedMint,
+    mint: &Pubkey,
+    feed_id_hex: Option<&str>,
+    oracle_policy: &OraclePolicy,
+    now: i64,
+) -> Result<()> {
+    let price = oracle_price(oracles, mint, feed_id_hex, oracle_policy, now)?;
+    check_peg(pegged, &price)
+}
+
+// Whether the two prices are within `bps` of the lower one
+fn within_bps(a: &CheckedPrice, b: &CheckedPrice, bps: u16) -> Result<bool> {
+    // Bring both prices to the finer exponent before comparing
+    let expo = a.expo.min(b.expo);
+    let a_price = rescale(a.price, a.expo, expo)?;
+    let b_price = rescale(b.price, b.expo, expo)?;
+
+    let diff = a_price
+        .abs_diff(b_price)
+        .checked_mul(BPS_DENOMINATOR)
+        .ok_or(ErrorCode::PricingOverflow)?;
+    let allowed = a_price
+        .min(b_price)
+        .checked_mul(bps as u128)
+        .ok_or(ErrorCode::PricingOverflow)?;
+    Ok(diff <= allowed)
+}
+
+// price * 10^(from - to), `to` must not be above `from`
+fn rescale(price: u128, from: i32, to: i32) -> Result<u128> {
+    u32::try_from(from as i64 - to as i64)
+        .ok()
+        .and_then(|shift| 10u128.checked_pow(shift))
+        .and_then(|multiplier| price.checked_mul(multiplier))
+        .ok_or(ErrorCode::PricingOverflow.into())
+}
+
+/// Which way to round when the value has more precision than micro-USD
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Value = price * 10^expo * amount * 10^-decimals, expressed in micro-USD.
+/// Works for either exponent sign and fails with `PricingOverflow` instead of truncating.
+pub fn usd_value_from_price(
+    price: u128,
+    expo: i32,
+    amount: u64,
+    decimals: u8,
+    rounding: Rounding,
+)->Result<u64>{
+    let numerator = price
+        .checked_mul(amount as u128)
+        .ok_or(ErrorCode::PricingOverflow)?;
+
+    // Net power of ten to apply to price * amount
+    let scale = expo as i64 + USD_DECIMALS - decimals as i64;
+
+    let value = if scale >= 0 {
+        let multiplier = u32::try_from(scale)
+            .ok()
+            .and_then(|s| 10u128.checked_pow(s));
+        match multiplier {
+            Some(multiplier) => numerator.checked_mul(multiplier),
+            // Only zero survives a multiplier that doesn't fit
+            None if numerator == 0 => Some(0),
+            None => None,
+        }
+        .ok_or(ErrorCode::PricingOverflow)?
+    } else {
+        let divisor = u32::try_from(-scale)
+            .ok()
+            .and_then(|s| 10u128.checked_pow(s));
+        match divisor {
+            Some(divisor) => {
+                let quotient = numerator / divisor;
+                if rounding == Rounding::Up && numerator % divisor != 0 {
+                    quotient + 1
+                } else {
+                    quotient
+                }
+            }
+            // Divisor exceeds any u128 numerator, so only the fractional part is left
+            None if rounding == Rounding::Up && numerator > 0 => 1,
+            None => 0,
+        }
+    };
+
+    Ok(u64::try_from(value).map_err(|_| ErrorCode::PricingOverflow)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{MintOracle, PeggedMint};
+
+    fn is_overflow(result: Result<u64>) -> bool {
+        is_overflow_ref(&result)
+    }
+
+    /// Exact value as (numerator, denominator) so results can be checked without the function under test,
+    /// `None` when the exact numerator doesn't fit in u128 either
+    fn exact(price: u128, expo: i32, amount: u64, decimals: u8) -> Option<(u128, u128)> {
+        let scale = expo as i64 + USD_DECIMALS - decimals as i64;
+        let numerator = price.checked_mul(amount as u128)?;
+        if scale >= 0 {
+            Some((numerator.checked_mul(10u128.checked_pow(scale as u32)?)?, 1))
+        } else {
+            Some((numerator, 10u128.pow((-scale) as u32)))
+        }
+    }
+
+    /// Orders overflow above every representable value
+    fn value_or_max(result: Result<u64>) -> u128 {
+        if is_overflow_ref(&result) {
+            return u128::MAX;
+        }
+        result.unwrap() as u128
+    }
+
+    fn is_overflow_ref(result: &Result<u64>) -> bool {
+        has_error(result, ErrorCode::PricingOverflow)
+    }
+
+    fn has_error<T>(result: &Result<T>, code: ErrorCode) -> bool {
+        match result {
+            Err(Error::AnchorError(e)) => e.error_code_number == u32::from(code),
+            _ => false,
+        }
+    }
+
+    /// Small deterministic xorshift generator, keeps the suite free of extra dependencies
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            let mut x = self.0;
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            self.0 = x;
+            x
+        }
+
+        fn below(&mut self, bound: u64) -> u64 {
+            self.next() % bound
+        }
+    }
+
+    #[test]
+    fn sol_price_with_negative_exponent() {
+        // $150.12345678 with expo -8, 1.5 SOL in lamports
+        let value = usd_value_from_price(15_012_345_678, -8, 1_500_000_000, 9, Rounding::Down).unwrap();
+        assert_eq!(value, 225_185_185);
+        let value = usd_value_from_price(15_012_345_678, -8, 1_500_000_000, 9, Rounding::Up).unwrap();
+        assert_eq!(value, 225_185_186);
+    }
+
+    #[test]
+    fn positive_exponent_scales_up() {
+        // 12 * 10^3 = $12,000 per whole token, 2 tokens with 6 decimals
+        let value = usd_value_from_price(12, 3, 2_000_000, 6, Rounding::Down).unwrap();
+        assert_eq!(value, 24_000_000_000);
+
+        // Same price expressed with exponent 0 must agree
+        let same = usd_value_from_price(12_000, 0, 2_000_000, 6, Rounding::Down).unwrap();
+        assert_eq!(value, same);
+    }
+
+    #[test]
+    fn zero_exponent_and_zero_decimals() {
+        assert_eq!(usd_value_from_price(3, 0, 7, 0, Rounding::Down).unwrap(), 21_000_000);
+    }
+
+    #[test]
+    fn zero_amount_is_zero_in_both_directions() {
+        for rounding in [Rounding::Down, Rounding::Up] {
+            assert_eq!(usd_value_from_price(1, 200, 0, 0, rounding).unwrap(), 0);
+            assert_eq!(usd_value_from_price(u64::MAX as u128, -200, 0, 255, rounding).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn tiny_values_round_to_zero_or_one() {
+        // One lamport at $1e-8 is far below a micro-USD
+        assert_eq!(usd_value_from_price(1, -8, 1, 9, Rounding::Down).unwrap(), 0);
+        assert_eq!(usd_value_from_price(1, -8, 1, 9, Rounding::Up).unwrap(), 1);
+
+        // Divisor too large to represent still rounds correctly
+        assert_eq!(usd_value_from_price(1, i32::MIN, 1, 255, Rounding::Down).unwrap(), 0);
+        assert_eq!(usd_value_from_price(1, i32::MIN, 1, 255, Rounding::Up).unwrap(), 1);
+    }
+
+    #[test]
+    fn result_above_u64_is_overflow() {
+        // Previously truncated by `as u64`
+        assert!(is_overflow(usd_value_from_price(u64::MAX as u128, 0, u64::MAX, 0, Rounding::Down)));
+        assert!(is_overflow(usd_value_from_price(1, 0, u64::MAX, 0, Rounding::Down)));
+
+        // Largest value that still fits
+        assert_eq!(
+            usd_value_from_price(1, -6, u64::MAX, 0, Rounding::Down).unwrap(),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn huge_positive_exponent_is_overflow() {
+        assert!(is_overflow(usd_value_from_price(1, 40, 1, 0, Rounding::Down)));
+        assert!(is_overflow(usd_value_from_price(1, i32::MAX, 1, 0, Rounding::Up)));
+    }
+
+    #[test]
+    fn product_above_u128_is_overflow() {
+        assert!(is_overflow(usd_value_from_price(u128::MAX, -30, 2, 0, Rounding::Down)));
+    }
+
+    #[test]
+    fn exhaustive_small_inputs_match_exact_value() {
+        for price in 0u128..=40 {
+            for expo in -12i32..=6 {
+                for amount in 0u64..=40 {
+                    for decimals in 0u8..=12 {
+                        let (numerator, denominator) = exact(price, expo, amount, decimals).unwrap();
+                        let floor = numerator / denominator;
+                        let ceil = floor + u128::from(numerator % denominator != 0);
+
+                        let down = usd_value_from_price(price, expo, amount, decimals, Rounding::Down);
+                        let up = usd_value_from_price(price, expo, amount, decimals, Rounding::Up);
+
+                        if ceil > u64::MAX as u128 {
+                            assert!(is_overflow(up));
+                        } else {
+                            assert_eq!(up.unwrap() as u128, ceil);
+                        }
+                        if floor > u64::MAX as u128 {
+                            assert!(is_overflow(down));
+                        } else {
+                            assert_eq!(down.unwrap() as u128, floor);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn property_up_is_down_or_down_plus_one() {
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..20_000 {
+            let price = rng.below(1 << 40) as u128;
+            let expo = rng.below(25) as i32 - 18;
+            let amount = rng.next() >> rng.below(64);
+            let decimals = rng.below(19) as u8;
+
+            let down = usd_value_from_price(price, expo, amount, decimals, Rounding::Down);
+            let up = usd_value_from_price(price, expo, amount, decimals, Rounding::Up);
+            let Some((numerator, denominator)) = exact(price, expo, amount, decimals) else {
+                // Exact value is beyond u128, so it is certainly beyond u64
+                assert!(is_overflow(down) && is_overflow(up));
+                continue;
+            };
+
+            match (down, up) {
+                (Ok(down), Ok(up)) => {
+                    let exact_division = numerator % denominator == 0;
+                    assert_eq!(up, down + u64::from(!exact_division));
+                    assert_eq!(down as u128, numerator / denominator);
+                }
+                (Ok(down), Err(e)) => {
+                    // Only possible when rounding up crosses u64::MAX
+                    assert_eq!(down, u64::MAX);
+                    assert!(is_overflow(Err(e)));
+                }
+                (Err(e), up) => {
+                    assert!(is_overflow(Err(e)));
+                    assert!(is_overflow(up));
+                    assert!(numerator / denominator > u64::MAX as u128);
+                }
+            }
+        }
+    }
 
-}
\ No newline at end of file
+    #[test]
+    fn property_monotonic_in_amount_and_price() {
+        let mut rng = XorShift(0xD1B5_4A32_D192_ED03);
+        for _ in 0..20_000 {
+            let price = 1 + rng.below(1 << 32) as u128;
+            let expo = rng.below(17) as i32 - 12;
+            let amount = rng.below(1 << 40);
+            let decimals = rng.below(13) as u8;
+            let bump = 1 + rng.below(1_000);
+
+            for rounding in [Rounding::Down, Rounding::Up] {
+                let base = value_or_max(usd_value_from_price(price, expo, amount, decimals, rounding));
+                let more_amount = value_or_max(usd_value_from_price(price, expo, amount + bump, decimals, rounding));
+                let more_price = value_or_max(usd_value_from_price(price + bump as u128, expo, amount, decimals, rounding));
+                assert!(more_amount >= base);
+                assert!(more_price >= base);
+            }
+        }
+    }
+
+    #[test]
+    fn property_exponent_and_decimals_shift_cancel() {
+        // Raising the exponent and the mint decimals by the same amount leaves the value unchanged
+        let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+        for _ in 0..20_000 {
+            let price = rng.below(1 << 40) as u128;
+            let expo = rng.below(17) as i32 - 12;
+            let amount = rng.below(1 << 40);
+            let decimals = rng.below(10) as u8;
+            let shift = rng.below(8) as u8;
+
+            for rounding in [Rounding::Down, Rounding::Up] {
+                let base = usd_value_from_price(price, expo, amount, decimals, rounding);
+                let shifted = usd_value_from_price(price, expo + shift as i32, amount, decimals + shift, rounding);
+                match (base, shifted) {
+                    (Ok(a), Ok(b)) => assert_eq!(a, b),
+                    (a, b) => assert!(is_overflow(a) && is_overflow(b)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn property_positive_exponent_matches_folded_price() {
+        let mut rng = XorShift(0x0123_4567_89AB_CDEF);
+        for _ in 0..20_000 {
+            let price = rng.below(1 << 24) as u128;
+            let expo = rng.below(8) as i32;
+            let amount = rng.below(1 << 32);
+            let decimals = rng.below(19) as u8;
+            let folded = price * 10u128.pow(expo as u32);
+
+            for rounding in [Rounding::Down, Rounding::Up] {
+                let a = usd_value_from_price(price, expo, amount, decimals, rounding);
+                let b = usd_value_from_price(folded, 0, amount, decimals, rounding);
+                match (a, b) {
+                    (Ok(a), Ok(b)) => assert_eq!(a, b),
+                    (a, b) => assert!(is_overflow(a) && is_overflow(b)),
+                }
+            }
+        }
+    }
+
+    // --- Oracle sources, built from locally constructed price accounts ---
+
+    const NOW: i64 = 1_700_000_000;
+    const PYTH_FEED: [u8; 32] = [7; 32];
+
+    fn pyth_update(price: i64, conf: u64, ema_price: i64, ema_conf: u64, publish_time: i64) -> PriceUpdateV2 {
+        PriceUpdateV2 {
+            write_authority: Pubkey::default(),
+            verification_level: VerificationLevel::Full,
+            price_message: pyth_solana_receiver_sdk::price_update::PriceFeedMessage {
+                feed_id: PYTH_FEED,
+                price,
+                conf,
+                exponent: -8,
+                publish_time,
+                prev_publish_time: publish_time - 1,
+                ema_price,
+                ema_conf,
+            },
+            posted_slot: 1,
+        }
+    }
+
+    /// Pull feed whose current result is `value` (18 decimals)
+    fn switchboard_feed(value: i128, std_dev: i128, timestamp: i64) -> PullFeedAccountData {
+        let mut feed: PullFeedAccountData = bytemuck::Zeroable::zeroed();
+        feed.result.value = value;
+        feed.result.std_dev = std_dev;
+        feed.result.slot = 100;
+        feed.result.submission_idx = 0;
+        feed.submission_timestamps[0] = timestamp;
+        feed
+    }
+
+    /// Account data for a pull feed. Host i128 is 16-byte aligned, so the buffer starts 8 bytes
+    /// into a 16-byte boundary to leave the struct after the discriminator aligned.
+    struct SwitchboardAccount {
+        words: Vec<u128>,
+    }
+
+    impl SwitchboardAccount {
+        const LEN: usize = 8 + std::mem::size_of::<PullFeedAccountData>();
+
+        fn new(value: i128, std_dev: i128, timestamp: i64) -> Self {
+            let mut words = vec![0u128; (Self::LEN + 8).div_ceil(16)];
+            let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut words);
+            bytes[8..16].copy_from_slice(&<PullFeedAccountData as switchboard_on_demand::Discriminator>::DISCRIMINATOR);
+            bytes[16..8 + Self::LEN].copy_from_slice(bytemuck::bytes_of(&switchboard_feed(value, std_dev, timestamp)));
+            Self { words }
+        }
+
+        fn data(&mut self) -> &mut [u8] {
+            &mut bytemuck::cast_slice_mut(&mut self.words)[8..8 + Self::LEN]
+        }
+    }
+
+    fn feed_config(feed_id: [u8; 32], price_source: PriceSource) -> FeedConfig {
+        FeedConfig {
+            feed_id,
+            max_age: 0,
+            max_conf_bps: 0,
+            use_upper_bound: false,
+            price_source,
+        }
+    }
+
+    fn policy(feeds: Vec<FeedConfig>, mints: Vec<MintOracle>) -> OraclePolicy {
+        OraclePolicy { feeds, mints, pegged: vec![] }
+    }
+
+    fn mint_oracle(mint: Pubkey, switchboard_key: Pubkey, max_divergence_bps: u16) -> MintOracle {
+        MintOracle {
+            mint,
+            primary: OracleSource { kind: OracleKind::Pyth, feed: PYTH_FEED },
+            fallback: Some(OracleSource { kind: OracleKind::Switchboard, feed: switchboard_key.to_bytes() }),
+            max_divergence_bps,
+        }
+    }
+
+    #[test]
+    fn pyth_quotes_spot_and_ema() {
+        let update = pyth_update(100_00000000, 1_000, 90_00000000, 2_000, NOW);
+        let oracle = PythOracle { price_update: &update, feed_id: PYTH_FEED };
+
+        let spot = oracle.quote(PriceSource::Spot).unwrap();
+        assert_eq!((spot.price, spot.conf, spot.source), (100_00000000, 1_000, PriceSource::Spot));
+        let ema = oracle.quote(PriceSource::Ema).unwrap();
+        assert_eq!((ema.price, ema.conf, ema.source), (90_00000000, 2_000, PriceSource::Ema));
+
+        let wrong_feed = PythOracle { price_update: &update, feed_id: [8; 32] };
+        assert!(wrong_feed.quote(PriceSource::Spot).is_err());
+    }
+
+    #[test]
+    fn pyth_partial_verification_is_rejected() {
+        let mut update = pyth_update(100_00000000, 0, 100_00000000, 0, NOW);
+        update.verification_level = VerificationLevel::Partial { num_signatures: 5 };
+        let oracle = PythOracle { price_update: &update, feed_id: PYTH_FEED };
+        assert!(has_error(&oracle.quote(PriceSource::Spot), ErrorCode::UnverifiedPrice));
+    }
+
+    #[test]
+    fn max_of_spot_and_ema_reports_the_higher_price() {
+        let update = pyth_update(100_00000000, 0, 120_00000000, 0, NOW);
+        let oracle = PythOracle { price_update: &update, feed_id: PYTH_FEED };
+        let policy = policy(vec![feed_config(PYTH_FEED, PriceSource::MaxOfSpotAndEma)], vec![]);
+
+        let price = checked_price(&oracle, &policy, NOW).unwrap();
+        assert_eq!(price.value_price, 120_00000000);
+        assert_eq!(price.source, PriceSource::Ema);
+        assert_eq!(price.oracle, OracleKind::Pyth);
+    }
+
+    #[test]
+    fn usd_value_follows_the_higher_ema_upper_bound() {
+        // Spot $100 ± $1, EMA $120 ± $2
+        let update = pyth_update(100_00000000, 1_00000000, 120_00000000, 2_00000000, NOW);
+        let oracle = PythOracle { price_update: &update, feed_id: PYTH_FEED };
+        let upper_bound = |price_source| {
+            let mut config = feed_config(PYTH_FEED, price_source);
+            config.use_upper_bound = true;
+            policy(vec![config], vec![])
+        };
+
+        // 1 SOL is charged against the USD caps at $122
+        let price = checked_price(&oracle, &upper_bound(PriceSource::MaxOfSpotAndEma), NOW).unwrap();
+        assert_eq!(price.source, PriceSource::Ema);
+        let value = usd_value_from_price(price.value_price, price.expo, 1_000_000_000, 9, Rounding::Up).unwrap();
+        assert_eq!(value, 122_000_000);
+
+        // Spot only ignores the EMA
+        let price = checked_price(&oracle, &upper_bound(PriceSource::Spot), NOW).unwrap();
+        let value = usd_value_from_price(price.value_price, price.expo, 1_000_000_000, 9, Rounding::Up).unwrap();
+        assert_eq!(value, 101_000_000);
+    }
+
+    #[test]
+    fn stale_and_wide_prices_are_rejected() {
+        let policy_default = policy(vec![], vec![]);
+        let stale = pyth_update(100_00000000, 0, 100_00000000, 0, NOW - MAXIMUM_AGE as i64 - 1);
+        let oracle = PythOracle { price_update: &stale, feed_id: PYTH_FEED };
+        assert!(has_error(&checked_price(&oracle, &policy_default, NOW), ErrorCode::StalePrice));
+
+        // 2% confidence against a 1% limit
+        let wide = pyth_update(100_00000000, 2_00000000, 100_00000000, 0, NOW);
+        let oracle = PythOracle { price_update: &wide, feed_id: PYTH_FEED };
+        let mut config = feed_config(PYTH_FEED, PriceSource::Spot);
+        config.max_conf_bps = 100;
+        let strict = policy(vec![config], vec![]);
+        assert!(has_error(&checked_price(&oracle, &strict, NOW), ErrorCode::LowConfidencePrice));
+    }
+
+    #[test]
+    fn switchboard_quotes_the_median_at_18_decimals() {
+        let feed = switchboard_feed(150 * 10i128.pow(18), 10i128.pow(17), NOW);
+        let oracle = SwitchboardOracle { key: Pubkey::new_unique(), feed: &feed };
+
+        let quote = oracle.quote(PriceSource::Ema).unwrap();
+        assert_eq!(quote.price, 150 * 10i128.pow(18));
+        assert_eq!(quote.conf, 10u128.pow(17));
+        assert_eq!(quote.expo, -18);
+        assert_eq!(quote.publish_time, NOW);
+        // No EMA on Switchboard, spot is reported
+        assert_eq!(quote.source, PriceSource::Spot);
+
+        // 1 SOL at $150
+        let price = checked_price(&oracle, &policy(vec![], vec![]), NOW).unwrap();
+        let value = usd_value_from_price(price.value_price, price.expo, 1_000_000_000, 9, Rounding::Up).unwrap();
+        assert_eq!(value, 150_000_000);
+    }
+
+    #[test]
+    fn switchboard_feed_without_result_is_rejected() {
+        let mut feed = switchboard_feed(150 * 10i128.pow(18), 0, NOW);
+        feed.result.slot = 0;
+        let oracle = SwitchboardOracle { key: Pubkey::new_unique(), feed: &feed };
+        assert!(has_error(&oracle.quote(PriceSource::Spot), ErrorCode::InvalidOracleAccount));
+    }
+
+    #[test]
+    fn fallback_prices_the_mint_when_primary_is_stale() {
+        let mint = Pubkey::new_unique();
+        let sb_key = Pubkey::new_unique();
+        let owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID;
+        let mut lamports = 0;
+        let mut account = SwitchboardAccount::new(150 * 10i128.pow(18), 0, NOW);
+        let sb_info = AccountInfo::new(&sb_key, false, false, &mut lamports, account.data(), &owner, false, 0);
+
+        let stale = pyth_update(150_00000000, 0, 150_00000000, 0, NOW - 3_600);
+        let oracles = OracleAccounts { price_update: Some(&stale), switchboard_feed: Some(&sb_info) };
+        let policy = policy(vec![], vec![mint_oracle(mint, sb_key, 100)]);
+
+        let price = oracle_price(&oracles, &mint, None, &policy, NOW).unwrap();
+        assert_eq!(price.oracle, OracleKind::Switchboard);
+
+        // Without the fallback account the primary's error is reported
+        let pyth_only = OracleAccounts { price_update: Some(&stale), switchboard_feed: None };
+        assert!(has_error(&oracle_price(&pyth_only, &mint, None, &policy, NOW), ErrorCode::StalePrice));
+    }
+
+    #[test]
+    fn missing_primary_does_not_fall_back() {
+        let mint = Pubkey::new_unique();
+        let sb_key = Pubkey::new_unique();
+        let owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID;
+        let mut lamports = 0;
+        let mut account = SwitchboardAccount::new(150 * 10i128.pow(18), 0, NOW);
+        let sb_info = AccountInfo::new(&sb_key, false, false, &mut lamports, account.data(), &owner, false, 0);
+
+        let oracles = OracleAccounts { price_update: None, switchboard_feed: Some(&sb_info) };
+        let policy = policy(vec![], vec![mint_oracle(mint, sb_key, 100)]);
+        assert!(has_error(&oracle_price(&oracles, &mint, None, &policy, NOW), ErrorCode::MissingOracleAccount));
+    }
+
+    #[test]
+    fn divergence_check_requires_the_fallback_account() {
+        let mint = Pubkey::new_unique();
+        let sb_key = Pubkey::new_unique();
+        let update = pyth_update(150_00000000, 0, 150_00000000, 0, NOW);
+        let pyth_only = OracleAccounts { price_update: Some(&update), switchboard_feed: None };
+
+        let strict = policy(vec![], vec![mint_oracle(mint, sb_key, 100)]);
+        assert!(has_error(&oracle_price(&pyth_only, &mint, None, &strict, NOW), ErrorCode::MissingOracleAccount));
+
+        // Without a divergence bound the primary alone is enough
+        let unchecked = policy(vec![], vec![mint_oracle(mint, sb_key, 0)]);
+        assert_eq!(oracle_price(&pyth_only, &mint, None, &unchecked, NOW).unwrap().oracle, OracleKind::Pyth);
+    }
+
+    #[test]
+    fn primary_is_used_when_both_agree() {
+        let mint = Pubkey::new_unique();
+        let sb_key = Pubkey::new_unique();
+        let owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID;
+        let mut lamports = 0;
+        // $150.50 on Switchboard vs $150 on Pyth, within 1%
+        let mut account = SwitchboardAccount::new(15_050 * 10i128.pow(16), 0, NOW);
+        let sb_info = AccountInfo::new(&sb_key, false, false, &mut lamports, account.data(), &owner, false, 0);
+
+        let update = pyth_update(150_00000000, 0, 150_00000000, 0, NOW);
+        let oracles = OracleAccounts { price_update: Some(&update), switchboard_feed: Some(&sb_info) };
+        let policy = policy(vec![], vec![mint_oracle(mint, sb_key, 100)]);
+
+        let price = oracle_price(&oracles, &mint, None, &policy, NOW).unwrap();
+        assert_eq!(price.oracle, OracleKind::Pyth);
+        assert_eq!(price.value_price, 150_00000000);
+    }
+
+    #[test]
+    fn diverging_oracles_are_rejected() {
+        let mint = Pubkey::new_unique();
+        let sb_key = Pubkey::new_unique();
+        let owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID;
+        let mut lamports = 0;
+        // $160 on Switchboard vs $150 on Pyth, more than 1% apart
+        let mut account = SwitchboardAccount::new(160 * 10i128.pow(18), 0, NOW);
+        let sb_info = AccountInfo::new(&sb_key, false, false, &mut lamports, account.data(), &owner, false, 0);
+
+        let update = pyth_update(150_00000000, 0, 150_00000000, 0, NOW);
+        let oracles = OracleAccounts { price_update: Some(&update), switchboard_feed: Some(&sb_info) };
+
+        let strict = policy(vec![], vec![mint_oracle(mint, sb_key, 100)]);
+        assert!(has_error(&oracle_price(&oracles, &mint, None, &strict, NOW), ErrorCode::PriceDivergence));
+
+        let unchecked = policy(vec![], vec![mint_oracle(mint, sb_key, 0)]);
+        assert!(oracle_price(&oracles, &mint, None, &unchecked, NOW).is_ok());
+    }
+
+    #[test]
+    fn switchboard_account_must_match_policy() {
+        let mint = Pubkey::new_unique();
+        let sb_key = Pubkey::new_unique();
+        let stale = pyth_update(150_00000000, 0, 150_00000000, 0, NOW - 3_600);
+        let policy = policy(vec![], vec![mint_oracle(mint, sb_key, 0)]);
+
+        // Right key, wrong owner
+        let fake_owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut account = SwitchboardAccount::new(150 * 10i128.pow(18), 0, NOW);
+        let info = AccountInfo::new(&sb_key, false, false, &mut lamports, account.data(), &fake_owner, false, 0);
+        let oracles = OracleAccounts { price_update: Some(&stale), switchboard_feed: Some(&info) };
+        assert!(oracle_price(&oracles, &mint, None, &policy, NOW).is_err());
+
+        let result = read_oracle(&oracles, policy.mints[0].fallback.as_ref().unwrap(), &policy, NOW);
+        assert!(has_error(&result, ErrorCode::InvalidOracleAccount));
+
+        // Right owner, different feed
+        let other_key = Pubkey::new_unique();
+        let owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID;
+        let mut lamports = 0;
+        let mut account = SwitchboardAccount::new(150 * 10i128.pow(18), 0, NOW);
+        let info = AccountInfo::new(&other_key, false, false, &mut lamports, account.data(), &owner, false, 0);
+        let oracles = OracleAccounts { price_update: Some(&stale), switchboard_feed: Some(&info) };
+        let result = read_oracle(&oracles, policy.mints[0].fallback.as_ref().unwrap(), &policy, NOW);
+        assert!(has_error(&result, ErrorCode::InvalidOracleAccount));
+    }
+
+    #[test]
+    fn divergence_compares_across_exponents() {
+        let pyth = CheckedPrice {
+            price: 100_00000000,
+            value_price: 100_00000000,
+            expo: -8,
+            source: PriceSource::Spot,
+            oracle: OracleKind::Pyth,
+        };
+        let switchboard = CheckedPrice {
+            price: 101 * 10u128.pow(18),
+            value_price: 101 * 10u128.pow(18),
+            expo: -18,
+            source: PriceSource::Spot,
+            oracle: OracleKind::Switchboard,
+        };
+        assert!(check_divergence(&pyth, &switchboard, 100).is_ok());
+        assert!(check_divergence(&switchboard, &pyth, 100).is_ok());
+        assert!(has_error(&check_divergence(&pyth, &switchboard, 99), ErrorCode::PriceDivergence));
+    }
+
+    fn usdc_peg(max_depeg_bps: u16) -> PeggedMint {
+        PeggedMint { mint: Pubkey::new_unique(), usd_rate: 1_000_000, max_depeg_bps }
+    }
+
+    fn usd_price(price: u128, expo: i32) -> CheckedPrice {
+        CheckedPrice { price, value_price: price, expo, source: PriceSource::Spot, oracle: OracleKind::Pyth }
+    }
+
+    #[test]
+    fn pegged_mint_values_at_fixed_rate() {
+        // 25.5 USDC at $1
+        let value = pegged_usd_value(&usdc_peg(0), 25_500_000, 6, Rounding::Up).unwrap();
+        assert_eq!(value, 25_500_000);
+
+        // Peg below $1, rounding applies to the fractional micro-USD
+        let peg = PeggedMint { mint: Pubkey::new_unique(), usd_rate: 999_999, max_depeg_bps: 0 };
+        assert_eq!(pegged_usd_value(&peg, 3, 6, Rounding::Down).unwrap(), 2);
+        assert_eq!(pegged_usd_value(&peg, 3, 6, Rounding::Up).unwrap(), 3);
+    }
+
+    #[test]
+    fn depeg_guard_compares_oracle_with_peg() {
+        let peg = usdc_peg(50);
+        // $0.9990 with expo -8 is 10 bps off
+        assert!(check_peg(&peg, &usd_price(99_900_000, -8)).is_ok());
+        // $0.9900 is 100 bps off
+        assert!(has_error(&check_peg(&peg, &usd_price(99_000_000, -8)), ErrorCode::MintDepegged));
+        // Above the peg counts too
+        assert!(has_error(&check_peg(&peg, &usd_price(101_000_000, -8)), ErrorCode::MintDepegged));
+    }
+
+    #[test]
+    fn depeg_guard_requires_the_oracle_accounts() {
+        let peg = usdc_peg(50);
+        let guarded = policy(vec![], vec![MintOracle {
+            mint: peg.mint,
+            primary: OracleSource { kind: OracleKind::Pyth, feed: PYTH_FEED },
+            fallback: None,
+            max_divergence_bps: 0,
+        }]);
+
+        let none = OracleAccounts { price_update: None, switchboard_feed: None };
+        let result = guard_peg(&none, &peg, &peg.mint, None, &guarded, NOW);
+        assert!(has_error(&result, ErrorCode::MissingOracleAccount));
+
+        let on_peg = pyth_update(99_990_000, 0, 99_990_000, 0, NOW);
+        let oracles = OracleAccounts { price_update: Some(&on_peg), switchboard_feed: None };
+        assert!(guard_peg(&oracles, &peg, &peg.mint, None, &guarded, NOW).is_ok());
+
+        let off_peg = pyth_update(98_000_000, 0, 98_000_000, 0, NOW);
+        let oracles = OracleAccounts { price_update: Some(&off_peg), switchboard_feed: None };
+        let result = guard_peg(&oracles, &peg, &peg.mint, None, &guarded, NOW);
+        assert!(has_error(&result, ErrorCode::MintDepegged));
+    }
+
+    #[test]
+    fn unconfigured_mint_needs_a_feed_id() {
+        let update = pyth_update(100_00000000, 0, 100_00000000, 0, NOW);
+        let oracles = OracleAccounts { price_update: Some(&update), switchboard_feed: None };
+        let result = oracle_price(&oracles, &Pubkey::new_unique(), None, &policy(vec![], vec![]), NOW);
+        assert!(has_error(&result, ErrorCode::MissingFeedId));
+    }
+}
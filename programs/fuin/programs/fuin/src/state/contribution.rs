@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+// Running total of what one depositor has put into a vault, per mint
+#[derive(InitSpace)]
+#[account]
+pub struct Contribution{
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+    // Pubkey::default() for SOL
+    pub mint: Pubkey,
+    // net of Token-2022 transfer fees
+    pub total_deposited: u64,
+    pub deposits: u32,
+    pub last_deposit_at: i64,
+    pub bump: u8,
+}
@@ -1,11 +1,15 @@
 use anchor_lang::prelude::*;
 
-use crate::state::LimitTracker;
+use crate::{error::ErrorCode, state::LimitTracker};
 
 pub const CAN_SWAP: u16 = 1;      // binary: 0001
 pub const CAN_TRANSFER: u16 = 2;  // binary: 0010
 pub const CAN_STAKE: u16 = 4;     // binary: 0100
 pub const CAN_LP: u16 = 8;        // binary: 1000
+pub const CAN_SUBDELEGATE: u16 = 16; // binary: 10000
+
+// Intent nonces are tracked in a sliding window of this many slots
+pub const INTENT_NONCE_WINDOW: u64 = 64;
 
 #[derive(InitSpace)]
 #[account]
@@ -20,6 +24,16 @@ pub struct Delegate{
     pub uses: u32,
     pub expiry: i64,
     pub is_active: bool,
+    // guardian opt-in for rotate_delegate_key
+    pub allow_key_rotation: bool,
+    // set for sub-delegates, spending also counts against this delegate
+    pub parent: Option<Pubkey>,
+    // vault.delegates_issued when this delegate was issued, and the parent's at the time for sub-delegates
+    pub issuance: u64,
+    pub parent_issuance: u64,
+    // lowest intent nonce still accepted, bit i of the bitmap marks floor + i as used
+    pub intent_nonce_floor: u64,
+    pub intent_nonce_bitmap: u64,
     pub nonce: u64,
     pub bump: u8,
 }
@@ -28,6 +42,36 @@ impl Delegate{
     pub fn has_permission(&self, perm: u16) -> bool {
         self.permissions & perm != 0
     }
+
+    /// Revoke (delegate_control status 0) deactivates and zeroes expiry, pause only deactivates
+    pub fn is_revoked(&self) -> bool {
+        !self.is_active && self.expiry == 0
+    }
+
+    /// Marks an intent nonce as used. Nonces may arrive out of order within the
+    /// window, anything below it is rejected as stale.
+    pub fn consume_intent_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(nonce >= self.intent_nonce_floor, ErrorCode::IntentNonceStale);
+
+        let mut offset = nonce - self.intent_nonce_floor;
+        if offset >= INTENT_NONCE_WINDOW {
+            // Slide the window up so `nonce` lands in its top slot
+            let shift = offset - (INTENT_NONCE_WINDOW - 1);
+            self.intent_nonce_bitmap = if shift >= INTENT_NONCE_WINDOW {
+                0
+            } else {
+                self.intent_nonce_bitmap >> shift
+            };
+            self.intent_nonce_floor = nonce - (INTENT_NONCE_WINDOW - 1);
+            offset = INTENT_NONCE_WINDOW - 1;
+        }
+
+        let bit = 1u64 << offset;
+        require!(self.intent_nonce_bitmap & bit == 0, ErrorCode::IntentNonceReused);
+        self.intent_nonce_bitmap |= bit;
+
+        Ok(())
+    }
 }
 
 impl LimitTracker for Delegate{
@@ -47,3 +91,118 @@ impl LimitTracker for Delegate{
         self.daily_spent = amount;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delegate() -> Delegate {
+        Delegate {
+            vault: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            permissions: CAN_TRANSFER,
+            daily_limit: 0,
+            daily_spent: 0,
+            last_reset_epoch: 0,
+            max_uses: 0,
+            uses: 0,
+            expiry: i64::MAX,
+            is_active: true,
+            allow_key_rotation: false,
+            parent: None,
+            issuance: 1,
+            parent_issuance: 0,
+            intent_nonce_floor: 0,
+            intent_nonce_bitmap: 0,
+            nonce: 0,
+            bump: 255,
+        }
+    }
+
+    fn has_error(result: &Result<()>, code: ErrorCode) -> bool {
+        match result {
+            Err(Error::AnchorError(e)) => e.error_code_number == u32::from(code),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn nonces_may_arrive_out_of_order_within_the_window() {
+        let mut d = delegate();
+        for nonce in [5, 2, 63, 0, 40] {
+            d.consume_intent_nonce(nonce).unwrap();
+        }
+        assert_eq!(d.intent_nonce_floor, 0);
+        assert_eq!(d.intent_nonce_bitmap, (1 << 5) | (1 << 2) | (1 << 63) | 1 | (1 << 40));
+    }
+
+    #[test]
+    fn reused_nonce_is_rejected() {
+        let mut d = delegate();
+        d.consume_intent_nonce(7).unwrap();
+        assert!(has_error(&d.consume_intent_nonce(7), ErrorCode::IntentNonceReused));
+
+        // Still rejected once the window has moved past it, as long as it's inside
+        d.consume_intent_nonce(60).unwrap();
+        d.consume_intent_nonce(66).unwrap();
+        assert!(has_error(&d.consume_intent_nonce(7), ErrorCode::IntentNonceReused));
+    }
+
+    #[test]
+    fn short_slide_keeps_the_marks_still_in_the_window() {
+        let mut d = delegate();
+        d.consume_intent_nonce(10).unwrap();
+        d.consume_intent_nonce(3).unwrap();
+
+        // 70 lands in the top slot, the window moves up by 7
+        d.consume_intent_nonce(70).unwrap();
+        assert_eq!(d.intent_nonce_floor, 7);
+        assert_eq!(d.intent_nonce_bitmap, (1 << 3) | (1 << 63));
+
+        assert!(has_error(&d.consume_intent_nonce(10), ErrorCode::IntentNonceReused));
+        assert!(has_error(&d.consume_intent_nonce(3), ErrorCode::IntentNonceStale));
+        d.consume_intent_nonce(8).unwrap();
+    }
+
+    #[test]
+    fn jump_of_a_full_window_clears_the_bitmap() {
+        let mut d = delegate();
+        for nonce in 0..64 {
+            d.consume_intent_nonce(nonce).unwrap();
+        }
+        assert_eq!(d.intent_nonce_bitmap, u64::MAX);
+
+        d.consume_intent_nonce(127).unwrap();
+        assert_eq!(d.intent_nonce_floor, 64);
+        assert_eq!(d.intent_nonce_bitmap, 1 << 63);
+        d.consume_intent_nonce(64).unwrap();
+
+        d.consume_intent_nonce(1_000).unwrap();
+        assert_eq!(d.intent_nonce_floor, 1_000 - 63);
+        assert_eq!(d.intent_nonce_bitmap, 1 << 63);
+    }
+
+    #[test]
+    fn nonce_below_the_floor_is_stale() {
+        let mut d = delegate();
+        d.consume_intent_nonce(200).unwrap();
+        assert_eq!(d.intent_nonce_floor, 137);
+
+        assert!(has_error(&d.consume_intent_nonce(136), ErrorCode::IntentNonceStale));
+        assert!(has_error(&d.consume_intent_nonce(0), ErrorCode::IntentNonceStale));
+        d.consume_intent_nonce(137).unwrap();
+    }
+
+    #[test]
+    fn nonces_near_u64_max_do_not_overflow() {
+        let mut d = delegate();
+        d.consume_intent_nonce(u64::MAX).unwrap();
+        assert_eq!(d.intent_nonce_floor, u64::MAX - 63);
+        assert_eq!(d.intent_nonce_bitmap, 1 << 63);
+
+        assert!(has_error(&d.consume_intent_nonce(u64::MAX), ErrorCode::IntentNonceReused));
+        d.consume_intent_nonce(u64::MAX - 1).unwrap();
+        d.consume_intent_nonce(u64::MAX - 63).unwrap();
+        assert!(has_error(&d.consume_intent_nonce(u64::MAX - 64), ErrorCode::IntentNonceStale));
+    }
+}
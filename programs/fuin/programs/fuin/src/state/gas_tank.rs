@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::state::LimitTracker;
+
+#[derive(InitSpace)]
+#[account]
+pub struct GasTank{
+    pub vault: Pubkey,
+    // lamports reimbursed to the relayer for each sponsored execute call
+    pub fee_per_call: u64,
+    // per epoch
+    pub daily_cap: u64,
+    pub daily_spent: u64,
+    pub last_reset_epoch: u64,
+    pub bump: u8,
+}
+
+impl LimitTracker for GasTank{
+    fn get_limit(&self) -> Option<u64> {
+        Some(self.daily_cap)
+    }
+
+    fn get_spent(&self) -> u64 {
+        self.daily_spent
+    }
+
+    fn set_spent(&mut self, amount: u64) {
+        self.daily_spent = amount;
+    }
+}
@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    Delegate, MintPolicy, OraclePolicy, PolicySet, ProgramPolicy, RecoveryConfig, RelayerPolicy,
+    RiskPolicy, SpendingPolicy, TimePolicy, Vault, VaultState, VAULT_VERSION,
+};
+
+// Account layouts as deployed before VAULT_VERSION 2, only read by the migrate_* instructions.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacySpendingPolicy{
+    pub daily_cap: u64,
+    pub per_tx_cap: u64,
+    pub daily_spent: u64,
+    pub last_reset_epoch: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyRiskPolicy{
+    pub max_slippage_bps: u16,
+    pub require_cosign_above: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyPolicySet{
+    pub spending: LegacySpendingPolicy,
+    pub programs: ProgramPolicy,
+    pub time: TimePolicy,
+    pub risk: LegacyRiskPolicy,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyVault{
+    pub version: u8,
+    pub state: VaultState,
+    pub guardian: Pubkey,
+    pub policies: LegacyPolicySet,
+    pub recovery: RecoveryConfig,
+    pub nonce: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyDelegate{
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub permissions: u16,
+    pub daily_limit: u64,
+    pub daily_spent: u64,
+    pub last_reset_epoch: u64,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub expiry: i64,
+    pub is_active: bool,
+    pub nonce: u64,
+    pub bump: u8,
+}
+
+// New fields start out disabled/empty, so a migrated vault behaves as it did before the upgrade
+impl From<LegacyVault> for Vault{
+    fn from(legacy: LegacyVault) -> Self {
+        Vault {
+            version: VAULT_VERSION,
+            state: legacy.state,
+            guardian: legacy.guardian,
+            policies: PolicySet {
+                spending: SpendingPolicy {
+                    daily_cap: legacy.policies.spending.daily_cap,
+                    per_tx_cap: legacy.policies.spending.per_tx_cap,
+                    daily_spent: legacy.policies.spending.daily_spent,
+                    last_reset_epoch: legacy.policies.spending.last_reset_epoch,
+                    uses_per_batch: 0,
+                    daily_usd_cap: 0,
+                    per_tx_usd_cap: 0,
+                    daily_usd_spent: 0,
+                },
+                programs: legacy.policies.programs,
+                time: legacy.policies.time,
+                risk: RiskPolicy {
+                    max_slippage_bps: legacy.policies.risk.max_slippage_bps,
+                    require_cosign_above: legacy.policies.risk.require_cosign_above,
+                    require_memo_above: 0,
+                },
+                relayers: RelayerPolicy {
+                    allow_list: Vec::new(),
+                },
+                oracle: OraclePolicy {
+                    feeds: Vec::new(),
+                    mints: Vec::new(),
+                    pegged: Vec::new(),
+                },
+                mints: MintPolicy {
+                    allow_list: Vec::new(),
+                    deny_list: Vec::new(),
+                    blocked_extensions: Vec::new(),
+                },
+            },
+            recovery: legacy.recovery,
+            token_accounts: Vec::new(),
+            committed_lamports: 0,
+            spend_ledger: None,
+            delegates_issued: 0,
+            nonce: legacy.nonce,
+            bump: legacy.bump,
+        }
+    }
+}
+
+impl From<LegacyDelegate> for Delegate{
+    fn from(legacy: LegacyDelegate) -> Self {
+        Delegate {
+            vault: legacy.vault,
+            authority: legacy.authority,
+            permissions: legacy.permissions,
+            daily_limit: legacy.daily_limit,
+            daily_spent: legacy.daily_spent,
+            last_reset_epoch: legacy.last_reset_epoch,
+            max_uses: legacy.max_uses,
+            uses: legacy.uses,
+            expiry: legacy.expiry,
+            is_active: legacy.is_active,
+            allow_key_rotation: false,
+            parent: None,
+            issuance: 0,
+            parent_issuance: 0,
+            intent_nonce_floor: 0,
+            intent_nonce_bitmap: 0,
+            nonce: legacy.nonce,
+            bump: legacy.bump,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_delegate_converts_field_for_field() {
+        let legacy = LegacyDelegate {
+            vault: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            permissions: 3,
+            daily_limit: 500,
+            daily_spent: 120,
+            last_reset_epoch: 7,
+            max_uses: 10,
+            uses: 4,
+            expiry: 1_700_000_000,
+            is_active: true,
+            nonce: 2,
+            bump: 254,
+        };
+        let bytes = legacy.try_to_vec().unwrap();
+        assert_eq!(bytes.len(), LegacyDelegate::INIT_SPACE);
+
+        let delegate = Delegate::from(LegacyDelegate::deserialize(&mut &bytes[..]).unwrap());
+        assert_eq!(delegate.vault, legacy.vault);
+        assert_eq!(delegate.daily_spent, 120);
+        assert_eq!(delegate.uses, 4);
+        assert_eq!((delegate.nonce, delegate.bump), (2, 254));
+        assert!(!delegate.allow_key_rotation);
+    }
+}
@@ -11,5 +11,23 @@ pub use policy::*;
 pub mod delegate;
 pub use delegate::*;
 
+pub mod legacy;
+pub use legacy::*;
+
 pub mod route;
-pub use route::*;
\ No newline at end of file
+pub use route::*;
+
+pub mod gas_tank;
+pub use gas_tank::*;
+
+pub mod spend_ledger;
+pub use spend_ledger::*;
+
+pub mod contribution;
+pub use contribution::*;
+
+pub mod schedule;
+pub use schedule::*;
+
+pub mod stream;
+pub use stream::*;
@@ -7,6 +7,12 @@ pub struct SpendingPolicy{
     pub per_tx_cap: u64,
     pub daily_spent: u64,
     pub last_reset_epoch: u64,
+    // uses an execute_batch call costs the delegate (0 = one per item)
+    pub uses_per_batch: u32,
+    // micro-USD caps on oracle-valued spends (0 = unlimited), reset with daily_spent
+    pub daily_usd_cap: u64,
+    pub per_tx_usd_cap: u64,
+    pub daily_usd_spent: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
@@ -29,7 +35,121 @@ pub struct RiskPolicy{
     pub max_slippage_bps: u16,
     // transaction above this amount needs guardian co-signature (0=disabled) 
     pub require_cosign_above: u64,
+    // transfers above this amount must carry a memo (0=disabled)
+    pub require_memo_above: u64,
+}
+
+// Relayers allowed to submit delegate actions, empty means anyone can relay.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RelayerPolicy{
+    #[max_len(8)]
+    pub allow_list: Vec<Pubkey>,
+}
+
+// Which Pyth price a feed is valued with, and so what the vault's USD caps are charged
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum PriceSource{
+    Spot,
+    // exponentially-weighted moving average, resists short spikes
+    Ema,
+    // whichever of spot and EMA gives the higher value
+    MaxOfSpotAndEma,
+}
+
+// Per feed oracle settings, feeds without an entry use the pricing.rs defaults.
+// Switchboard feeds only have a spot price, so `price_source` doesn't apply to them.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct FeedConfig{
+    pub feed_id: [u8; 32],
+    // max price age in seconds (0 = default)
+    pub max_age: u64,
+    // max confidence / price in basis points (0 = unchecked)
+    pub max_conf_bps: u16,
+    // value spends at price + conf so limits err on the safe side
+    pub use_upper_bound: bool,
+    pub price_source: PriceSource,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum OracleKind{
+    Pyth,
+    // Switchboard On-Demand pull feed
+    Switchboard,
+    // guardian-set rate from the pegged mint list, only ever reported, never a configured source
+    Peg,
+    // execution wasn't valued (plain SOL transfers), only ever reported
+    Unpriced,
+}
+
+impl OracleKind{
+    /// Whether a mint's oracle policy can name this kind as a price source
+    pub fn is_price_source(&self) -> bool {
+        matches!(self, OracleKind::Pyth | OracleKind::Switchboard)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct OracleSource{
+    pub kind: OracleKind,
+    // Pyth feed id, or the Switchboard pull feed account address
+    pub feed: [u8; 32],
+}
+
+// Oracles a mint is valued with, the fallback is used when the primary can't give a usable price
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MintOracle{
+    pub mint: Pubkey,
+    pub primary: OracleSource,
+    pub fallback: Option<OracleSource>,
+    // max gap between primary and fallback prices in basis points, when both are usable (0 = unchecked)
+    pub max_divergence_bps: u16,
+}
+
+// Mint valued at a fixed rate instead of an oracle, e.g. USDC/USDT
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PeggedMint{
+    pub mint: Pubkey,
+    // micro-USD per whole token
+    pub usd_rate: u64,
+    // max oracle deviation from the peg in basis points, the oracle accounts are then required (0 = no guard)
+    pub max_depeg_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct OraclePolicy{
+    // keyed by Pyth feed id or Switchboard feed address
+    #[max_len(8)]
+    pub feeds: Vec<FeedConfig>,
+    #[max_len(8)]
+    pub mints: Vec<MintOracle>,
+    #[max_len(8)]
+    pub pegged: Vec<PeggedMint>,
+}
 
+impl OraclePolicy{
+    pub fn feed_config(&self, feed_id: &[u8; 32]) -> Option<&FeedConfig> {
+        self.feeds.iter().find(|feed| &feed.feed_id == feed_id)
+    }
+
+    pub fn mint_oracle(&self, mint: &Pubkey) -> Option<&MintOracle> {
+        self.mints.iter().find(|oracle| &oracle.mint == mint)
+    }
+
+    pub fn pegged_mint(&self, mint: &Pubkey) -> Option<&PeggedMint> {
+        self.pegged.iter().find(|pegged| &pegged.mint == mint)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MintPolicy{
+    // Mints delegates can spend or receive, empty means any mint
+    #[max_len(16)]
+    pub allow_list: Vec<Pubkey>,
+    #[max_len(8)]
+    pub deny_list: Vec<Pubkey>,
+    // Token-2022 extension type ids (spl_token_2022 ExtensionType) delegates can't move
+    #[max_len(8)]
+    pub blocked_extensions: Vec<u16>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
@@ -38,4 +158,7 @@ pub struct PolicySet{
     pub programs: ProgramPolicy,
     pub time: TimePolicy,
     pub risk: RiskPolicy,
+    pub relayers: RelayerPolicy,
+    pub oracle: OraclePolicy,
+    pub mints: MintPolicy,
 }
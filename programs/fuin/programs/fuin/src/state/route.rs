@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 
 use crate::state::delegate::{CAN_SWAP, CAN_STAKE, CAN_TRANSFER};
 
+/// What an intent asks the vault to do. `execute_intent` only executes `Transfer` so far.
 #[derive(AnchorDeserialize, AnchorSerialize, Clone)]
 pub enum Route{
     Transfer {
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+// Recurring payment out of a vault, set up by the guardian and cranked by anyone
+#[derive(InitSpace)]
+#[account]
+pub struct Schedule{
+    pub vault: Pubkey,
+    // paid directly for SOL, through its associated token account for SPL
+    pub recipient: Pubkey,
+    // Pubkey::default() for SOL
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub interval_seconds: i64,
+    pub start_time: i64,
+    // no payment falls due after this (0 = open-ended)
+    pub end_time: i64,
+    // 0 = unlimited
+    pub max_payments: u32,
+    pub payments_made: u32,
+    pub next_payment_at: i64,
+    pub nonce: u64,
+    pub bump: u8,
+}
+
+impl Schedule{
+    pub fn is_complete(&self) -> bool {
+        (self.max_payments > 0 && self.payments_made >= self.max_payments)
+            || (self.end_time > 0 && self.next_payment_at > self.end_time)
+    }
+}
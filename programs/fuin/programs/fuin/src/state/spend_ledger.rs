@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+pub const SPEND_LEDGER_CAPACITY: usize = 64;
+
+pub const ROUTE_KIND_TRANSFER: u8 = 0;
+pub const ROUTE_KIND_SPL_TRANSFER: u8 = 1;
+pub const ROUTE_KIND_SWAP: u8 = 2;
+pub const ROUTE_KIND_SCHEDULE: u8 = 3;
+pub const ROUTE_KIND_STREAM: u8 = 4;
+
+#[zero_copy]
+pub struct SpendEntry {
+    pub slot: u64,
+    pub amount: u64,
+    // micro-USD, 0 for Unpriced entries
+    pub usd_value: u64,
+    // the schedule or stream account for ROUTE_KIND_SCHEDULE and ROUTE_KIND_STREAM
+    pub delegate: Pubkey,
+    // Pubkey::default() for SOL
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub route_kind: u8,
+    // PriceSource discriminant the usd_value was computed with, meaningless for Unpriced entries
+    pub price_source: u8,
+    // OracleKind discriminant of the oracle that priced it, Unpriced when nothing did
+    pub oracle_kind: u8,
+    pub _padding: [u8; 5],
+}
+
+// Fixed-size ring buffer of the most recent executions for a vault
+#[account(zero_copy)]
+pub struct SpendLedger {
+    pub vault: Pubkey,
+    // next entry to overwrite
+    pub head: u32,
+    // number of valid entries, stops growing at capacity
+    pub len: u32,
+    pub entries: [SpendEntry; SPEND_LEDGER_CAPACITY],
+}
+
+impl SpendLedger {
+    pub const SPACE: usize = 8 + std::mem::size_of::<SpendLedger>();
+
+    pub fn push(&mut self, entry: SpendEntry) {
+        let head = self.head as usize;
+        self.entries[head] = entry;
+        self.head = ((head + 1) % SPEND_LEDGER_CAPACITY) as u32;
+        if (self.len as usize) < SPEND_LEDGER_CAPACITY {
+            self.len += 1;
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+// Linear SOL stream out of a vault. The unwithdrawn total stays in the vault,
+// counted in `Vault::committed_lamports`. Cancelling moves `end_time` up to the cancel time.
+#[derive(InitSpace)]
+#[account]
+pub struct Stream{
+    pub vault: Pubkey,
+    pub recipient: Pubkey,
+    pub rate_per_second: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub withdrawn: u64,
+    pub nonce: u64,
+    pub bump: u8,
+}
+
+impl Stream{
+    pub fn total(&self) -> Result<u64> {
+        self.vested_at(self.end_time)
+    }
+
+    /// Lamports vested by `now`, capped at the end time
+    pub fn vested_at(&self, now: i64) -> Result<u64> {
+        let elapsed = now.clamp(self.start_time, self.end_time) - self.start_time;
+        self.rate_per_second
+            .checked_mul(elapsed as u64)
+            .ok_or_else(|| error!(ErrorCode::Overflow))
+    }
+
+    /// Vested but not yet withdrawn
+    pub fn withdrawable_at(&self, now: i64) -> Result<u64> {
+        Ok(self.vested_at(now)?.saturating_sub(self.withdrawn))
+    }
+}
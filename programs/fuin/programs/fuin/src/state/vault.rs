@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
 
-use crate::state::{LimitTracker, PolicySet};
+use crate::{error::ErrorCode, state::{LimitTracker, PolicySet}};
+
+// Bumped on layout changes, older accounts are upgraded by migrate_vault
+pub const VAULT_VERSION: u8 = 2;
 
 #[derive(InitSpace)]
 #[account]
@@ -10,6 +13,15 @@ pub struct Vault{
     pub guardian: Pubkey,
     pub policies: PolicySet,
     pub recovery: RecoveryConfig,
+    // Vault-owned associated token accounts
+    #[max_len(16)]
+    pub token_accounts: Vec<VaultTokenAccount>,
+    // lamports owed to streams, not available for spending or withdrawal
+    pub committed_lamports: u64,
+    // set by init_spend_ledger, every execution must then record into it
+    pub spend_ledger: Option<Pubkey>,
+    // bumped per issued delegate, lets sub-delegates tell a reissued parent from the original
+    pub delegates_issued: u64,
     pub nonce:u64,
     pub bump:u8,
 }
@@ -21,6 +33,39 @@ pub enum VaultState{
     Draining,
 }
 
+pub const MAX_VAULT_TOKEN_ACCOUNTS: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub struct VaultTokenAccount{
+    pub mint: Pubkey,
+    pub address: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl Vault{
+    pub fn token_account(&self, mint: &Pubkey)->Option<&VaultTokenAccount>{
+        self.token_accounts.iter().find(|t| t.mint == *mint)
+    }
+
+    // Lists the ATA unless it already is; false if the registry is full
+    pub fn register_token_account(&mut self, entry: VaultTokenAccount)->bool{
+        if self.token_account(&entry.mint).is_some() {
+            return true;
+        }
+        if self.token_accounts.len() >= MAX_VAULT_TOKEN_ACCOUNTS {
+            return false;
+        }
+        self.token_accounts.push(entry);
+        true
+    }
+
+    // Next delegate issuance number, 0 is left for delegates issued before the counter existed
+    pub fn issue_delegate(&mut self)->Result<u64>{
+        self.delegates_issued = self.delegates_issued.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        Ok(self.delegates_issued)
+    }
+}
+
 impl LimitTracker for Vault{
     fn get_limit(&self)->Option<u64> {
         Some(self.policies.spending.daily_cap)
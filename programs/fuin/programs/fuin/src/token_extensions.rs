@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    state::Mint,
+};
+
+use crate::error::ErrorCode;
+
+/// Rejects Token-2022 mints carrying any of the vault's blocked extensions.
+/// Legacy Token program mints have no extensions and always pass.
+pub fn check_blocked_extensions(mint: &AccountInfo, blocked: &[u16]) -> Result<()> {
+    if blocked.is_empty() || mint.owner != &spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    blocked_extension(&data, blocked)?.map_or(Ok(()), |extension| {
+        msg!("Mint carries blocked extension {:?}", extension);
+        err!(ErrorCode::MintExtensionBlocked)
+    })
+}
+
+/// Fee Token-2022 withholds from a transfer of `amount`, 0 for mints without a transfer fee
+pub fn transfer_fee(mint: &AccountInfo, epoch: u64, amount: u64) -> Result<u64> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = mint.try_borrow_data()?;
+    fee_from_mint_data(&data, epoch, amount)
+}
+
+fn blocked_extension(data: &[u8], blocked: &[u16]) -> Result<Option<ExtensionType>> {
+    let mint = StateWithExtensions::<Mint>::unpack(data)?;
+    Ok(mint
+        .get_extension_types()?
+        .into_iter()
+        .find(|extension| blocked.contains(&u16::from(*extension))))
+}
+
+fn fee_from_mint_data(data: &[u8], epoch: u64, amount: u64) -> Result<u64> {
+    let mint = StateWithExtensions::<Mint>::unpack(data)?;
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_epoch_fee(epoch, amount)
+            .ok_or_else(|| error!(ErrorCode::Overflow)),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Extension type ids a guardian may block, anything spl_token_2022 knows about
+pub fn is_known_extension(extension: u16) -> bool {
+    ExtensionType::try_from(extension).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        permanent_delegate::PermanentDelegate, BaseStateWithExtensionsMut, StateWithExtensionsMut,
+    };
+    use anchor_lang::solana_program::program_option::COption;
+
+    fn mint_with(extensions: &[ExtensionType], init: impl FnOnce(&mut StateWithExtensionsMut<Mint>)) -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        init(&mut state);
+        state.base = Mint {
+            mint_authority: COption::None,
+            supply: 0,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    fn fee_mint(bps: u16, maximum_fee: u64) -> Vec<u8> {
+        mint_with(&[ExtensionType::TransferFeeConfig], |state| {
+            let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+            config.older_transfer_fee.transfer_fee_basis_points = bps.into();
+            config.older_transfer_fee.maximum_fee = maximum_fee.into();
+            config.newer_transfer_fee = config.older_transfer_fee;
+        })
+    }
+
+    #[test]
+    fn transfer_fee_is_withheld_from_gross_amount() {
+        let data = fee_mint(100, u64::MAX);
+        assert_eq!(fee_from_mint_data(&data, 0, 1_000_000).unwrap(), 10_000);
+        // Rounds up
+        assert_eq!(fee_from_mint_data(&data, 0, 1).unwrap(), 1);
+        assert_eq!(fee_from_mint_data(&data, 0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn transfer_fee_is_capped_at_maximum() {
+        let data = fee_mint(500, 2_000);
+        assert_eq!(fee_from_mint_data(&data, 0, 1_000_000).unwrap(), 2_000);
+    }
+
+    #[test]
+    fn mint_without_fee_config_charges_nothing() {
+        let data = mint_with(&[], |_| {});
+        assert_eq!(fee_from_mint_data(&data, 0, 1_000_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn blocked_extension_is_found() {
+        let data = mint_with(&[ExtensionType::PermanentDelegate], |state| {
+            state.init_extension::<PermanentDelegate>(true).unwrap();
+        });
+        let permanent_delegate = u16::from(ExtensionType::PermanentDelegate);
+        let transfer_hook = u16::from(ExtensionType::TransferHook);
+
+        assert_eq!(
+            blocked_extension(&data, &[transfer_hook, permanent_delegate]).unwrap(),
+            Some(ExtensionType::PermanentDelegate)
+        );
+        assert_eq!(blocked_extension(&data, &[transfer_hook]).unwrap(), None);
+    }
+
+    #[test]
+    fn unknown_extension_ids_are_rejected() {
+        assert!(is_known_extension(u16::from(ExtensionType::PermanentDelegate)));
+        assert!(!is_known_extension(u16::MAX));
+    }
+}
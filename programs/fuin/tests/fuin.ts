@@ -2,17 +2,41 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Fuin } from "../target/types/fuin";
 import {
+  ComputeBudgetProgram,
+  Ed25519Program,
   Keypair,
   LAMPORTS_PER_SOL,
   PublicKey,
   SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   Transaction,
   sendAndConfirmTransaction,
 } from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
 import { assert } from "chai";
 
 const CAN_SWAP = 1;
 const CAN_TRANSFER = 2;
+const CAN_SUBDELEGATE = 16;
+
+// OracleKind discriminant recorded in spend ledger entries
+const ORACLE_KIND_UNPRICED = 3;
+
+const MEMO_PROGRAM_ID = new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 
 // Scaled-down amounts for devnet (conserve SOL)
 const SOL = (n: number) => new anchor.BN(n * LAMPORTS_PER_SOL);
@@ -181,7 +205,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
     const amount = SOL(0.01);
 
     await program.methods
-      .executeTransfer(vaultNonce, delegateNonce, amount)
+      .executeTransfer(vaultNonce, delegateNonce, amount, null)
       .accounts({
         relayer: guardian.publicKey,
         delegateKey: agent.publicKey,
@@ -201,7 +225,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
   it("B5. Per-tx cap exceeded", async () => {
     // Lower per_tx_cap to 0.005 SOL
     await program.methods
-      .updateVault(vaultNonce, null, SOL(0.005), null, null)
+      .updateVault(vaultNonce, null, SOL(0.005), null, null, null, null, null)
       .accounts({
         guardian: guardian.publicKey,
         vault: vaultPda,
@@ -210,7 +234,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
 
     try {
       await program.methods
-        .executeTransfer(vaultNonce, delegateNonce, SOL(0.01))
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.01), null)
         .accounts({
           relayer: guardian.publicKey,
           delegateKey: agent.publicKey,
@@ -229,7 +253,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
 
     // Restore per_tx_cap
     await program.methods
-      .updateVault(vaultNonce, null, SOL(0.5), null, null)
+      .updateVault(vaultNonce, null, SOL(0.5), null, null, null, null, null)
       .accounts({
         guardian: guardian.publicKey,
         vault: vaultPda,
@@ -271,7 +295,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
 
     try {
       await program.methods
-        .executeTransfer(vaultNonce, swapDelegateNonce, SOL(0.01))
+        .executeTransfer(vaultNonce, swapDelegateNonce, SOL(0.01), null)
         .accounts({
           relayer: guardian.publicKey,
           delegateKey: swapAgent.publicKey,
@@ -300,7 +324,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
 
     try {
       await program.methods
-        .executeTransfer(vaultNonce, delegateNonce, SOL(0.01))
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.01), null)
         .accounts({
           relayer: guardian.publicKey,
           delegateKey: agent.publicKey,
@@ -328,7 +352,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
       .rpc();
 
     await program.methods
-      .executeTransfer(vaultNonce, delegateNonce, SOL(0.01))
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.01), null)
       .accounts({
         relayer: guardian.publicKey,
         delegateKey: agent.publicKey,
@@ -358,7 +382,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
 
     try {
       await program.methods
-        .executeTransfer(vaultNonce, delegateNonce, SOL(0.01))
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.01), null)
         .accounts({
           relayer: guardian.publicKey,
           delegateKey: agent.publicKey,
@@ -386,7 +410,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
       .rpc();
 
     await program.methods
-      .executeTransfer(vaultNonce, delegateNonce, SOL(0.01))
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.01), null)
       .accounts({
         relayer: guardian.publicKey,
         delegateKey: agent.publicKey,
@@ -411,7 +435,7 @@ describe("Fuin - Delegate Model (Devnet)", function () {
 
     try {
       await program.methods
-        .executeTransfer(vaultNonce, delegateNonce, SOL(0.01))
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.01), null)
         .accounts({
           relayer: guardian.publicKey,
           delegateKey: agent.publicKey,
@@ -435,9 +459,12 @@ describe("Fuin - Delegate Model (Devnet)", function () {
   it("B10. Update vault", async () => {
     const newDailyCap = SOL(2);
     const newPerTxCap = SOL(0.2);
+    // micro-USD
+    const newDailyUsdCap = new BN(500_000_000);
+    const newPerTxUsdCap = new BN(50_000_000);
 
     await program.methods
-      .updateVault(vaultNonce, newDailyCap, newPerTxCap, null, null)
+      .updateVault(vaultNonce, newDailyCap, newPerTxCap, null, null, null, newDailyUsdCap, newPerTxUsdCap)
       .accounts({
         guardian: guardian.publicKey,
         vault: vaultPda,
@@ -447,6 +474,8 @@ describe("Fuin - Delegate Model (Devnet)", function () {
     const vault = await program.account.vault.fetch(vaultPda);
     assert.ok(vault.policies.spending.dailyCap.eq(newDailyCap));
     assert.ok(vault.policies.spending.perTxCap.eq(newPerTxCap));
+    assert.ok(vault.policies.spending.dailyUsdCap.eq(newDailyUsdCap));
+    assert.ok(vault.policies.spending.perTxUsdCap.eq(newPerTxUsdCap));
   });
 
   it("B11. Withdraw", async () => {
@@ -465,6 +494,30 @@ describe("Fuin - Delegate Model (Devnet)", function () {
     const balanceAfter = await provider.connection.getBalance(vaultPda);
     assert.equal(balanceBefore - balanceAfter, 0.05 * LAMPORTS_PER_SOL);
   });
+
+  it("B12. Close revoked delegate", async () => {
+    // Delegate was revoked in B9, so the guardian can reclaim its rent
+    const guardianBefore = await provider.connection.getBalance(
+      guardian.publicKey
+    );
+
+    await program.methods
+      .closeDelegate(vaultNonce, delegateNonce)
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+      })
+      .rpc();
+
+    const delegateInfo = await provider.connection.getAccountInfo(delegatePda);
+    assert.isNull(delegateInfo);
+
+    const guardianAfter = await provider.connection.getBalance(
+      guardian.publicKey
+    );
+    assert.ok(guardianAfter > guardianBefore);
+  });
 });
 
 // ─── Policy Enforcement & Limit Exhaustion ───────────────────────────────────
@@ -547,6 +600,83 @@ describe("Fuin - Policy Enforcement (Devnet)", function () {
     return { vaultPda, vaultNonce, delegatePda, delegateNonce, agent };
   }
 
+  /** New 6-decimal mint with the guardian as mint authority */
+  function newMint(tokenProgram: PublicKey = TOKEN_PROGRAM_ID) {
+    return createMint(
+      provider.connection,
+      guardian.payer,
+      guardian.publicKey,
+      null,
+      6,
+      undefined,
+      undefined,
+      tokenProgram
+    );
+  }
+
+  /** New mint with `amount` tokens minted into the vault's associated token account */
+  async function fundedVaultMint(
+    vaultPda: PublicKey,
+    amount: number,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+  ) {
+    const mint = await newMint(tokenProgram);
+    const vaultAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      guardian.payer,
+      mint,
+      vaultPda,
+      true,
+      undefined,
+      undefined,
+      tokenProgram
+    );
+    await mintTo(
+      provider.connection,
+      guardian.payer,
+      mint,
+      vaultAta.address,
+      guardian.payer,
+      amount,
+      [],
+      undefined,
+      tokenProgram
+    );
+    const guardianAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      guardian.payer,
+      mint,
+      guardian.publicKey,
+      false,
+      undefined,
+      undefined,
+      tokenProgram
+    );
+    return { mint, vaultAta: vaultAta.address, guardianAta: guardianAta.address };
+  }
+
+  /** Events the program emitted through emit_cpi! in a confirmed transaction */
+  async function cpiEvents(signature: string) {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const keys = tx.transaction.message.staticAccountKeys;
+    const events = [];
+    for (const inner of tx.meta.innerInstructions ?? []) {
+      for (const ix of inner.instructions) {
+        if (!keys[ix.programIdIndex].equals(program.programId)) continue;
+        // Self-CPI data is the 8-byte event instruction tag followed by the encoded event
+        const data = anchor.utils.bytes.bs58.decode(ix.data);
+        const event = program.coder.events.decode(
+          anchor.utils.bytes.base64.encode(Buffer.from(data.subarray(8)))
+        );
+        if (event) events.push(event);
+      }
+    }
+    return events;
+  }
+
   function transferAccounts(
     vaultPda: PublicKey,
     delegatePda: PublicKey,
@@ -609,7 +739,7 @@ describe("Fuin - Policy Enforcement (Devnet)", function () {
       });
 
     await program.methods
-      .executeTransfer(vaultNonce, delegateNonce, SOL(0.01))
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.01), null)
       .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
       .signers([agent])
       .rpc();
@@ -632,7 +762,7 @@ describe("Fuin - Policy Enforcement (Devnet)", function () {
 
     // First: 0.04 SOL — succeed
     await program.methods
-      .executeTransfer(vaultNonce, delegateNonce, SOL(0.04))
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.04), null)
       .accounts(accounts)
       .signers([agent])
       .rpc();
@@ -640,7 +770,7 @@ describe("Fuin - Policy Enforcement (Devnet)", function () {
     // Second: 0.02 SOL — fail (0.04 + 0.02 > 0.05)
     try {
       await program.methods
-        .executeTransfer(vaultNonce, delegateNonce, SOL(0.02))
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.02), null)
         .accounts(accounts)
         .signers([agent])
         .rpc();
@@ -664,7 +794,7 @@ describe("Fuin - Policy Enforcement (Devnet)", function () {
 
     // First: 0.02 SOL — succeed
     await program.methods
-      .executeTransfer(vaultNonce, delegateNonce, SOL(0.02))
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.02), null)
       .accounts(accounts)
       .signers([agent])
       .rpc();
@@ -672,7 +802,7 @@ describe("Fuin - Policy Enforcement (Devnet)", function () {
     // Second: 0.02 SOL — fail (0.02 + 0.02 > 0.03)
     try {
       await program.methods
-        .executeTransfer(vaultNonce, delegateNonce, SOL(0.02))
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.02), null)
         .accounts(accounts)
         .signers([agent])
         .rpc();
@@ -697,14 +827,14 @@ describe("Fuin - Policy Enforcement (Devnet)", function () {
 
     // Use 1 — succeed
     await program.methods
-      .executeTransfer(vaultNonce, delegateNonce, amount)
+      .executeTransfer(vaultNonce, delegateNonce, amount, null)
       .accounts(accounts)
       .signers([agent])
       .rpc();
 
     // Use 2 — succeed
     await program.methods
-      .executeTransfer(vaultNonce, delegateNonce, amount)
+      .executeTransfer(vaultNonce, delegateNonce, amount, null)
       .accounts(accounts)
       .signers([agent])
       .rpc();
@@ -715,7 +845,7 @@ describe("Fuin - Policy Enforcement (Devnet)", function () {
     // Use 3 — fail
     try {
       await program.methods
-        .executeTransfer(vaultNonce, delegateNonce, amount)
+        .executeTransfer(vaultNonce, delegateNonce, amount, null)
         .accounts(accounts)
         .signers([agent])
         .rpc();
@@ -724,4 +854,1592 @@ describe("Fuin - Policy Enforcement (Devnet)", function () {
       assert.include(error.message, "MaxUsesExceeded");
     }
   });
+
+  it("P5. Crank cannot close an unexpired delegate", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    const cranker = Keypair.generate();
+    try {
+      await program.methods
+        .crankCloseDelegate(vaultNonce, delegateNonce)
+        .accounts({
+          cranker: cranker.publicKey,
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          delegate: delegatePda,
+        })
+        .signers([cranker])
+        .rpc();
+      assert.fail("Should have failed due to active delegate");
+    } catch (error: any) {
+      assert.include(error.message, "DelegateStillActive");
+    }
+  });
+
+  it("P6. Update delegate renews exhausted uses in place", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 1,
+      });
+
+    const accounts = transferAccounts(vaultPda, delegatePda, agent.publicKey);
+    const amount = SOL(0.001);
+
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, amount, null)
+      .accounts(accounts)
+      .signers([agent])
+      .rpc();
+
+    // Raise max uses, extend expiry and reset the counter on the same PDA
+    await program.methods
+      .updateDelegate(
+        vaultNonce,
+        delegateNonce,
+        null,
+        null,
+        3,
+        new anchor.BN(2 * 86400),
+        null,
+        true
+      )
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+      })
+      .rpc();
+
+    let delegate = await program.account.delegate.fetch(delegatePda);
+    assert.equal(delegate.uses, 0);
+    assert.equal(delegate.maxUses, 3);
+
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, amount, null)
+      .accounts(accounts)
+      .signers([agent])
+      .rpc();
+
+    delegate = await program.account.delegate.fetch(delegatePda);
+    assert.equal(delegate.uses, 1);
+  });
+
+  it("P7. Delegate rotates its own key, counters carry over", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.001), null)
+      .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+      .signers([agent])
+      .rpc();
+
+    // Guardian opts the delegate into rotation
+    await program.methods
+      .updateDelegate(vaultNonce, delegateNonce, null, null, null, null, true, false)
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+      })
+      .rpc();
+
+    const newAgent = Keypair.generate();
+    await program.methods
+      .rotateDelegateKey(vaultNonce, delegateNonce)
+      .accounts({
+        delegateKey: agent.publicKey,
+        newDelegateKey: newAgent.publicKey,
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+      })
+      .signers([agent, newAgent])
+      .rpc();
+
+    const delegate = await program.account.delegate.fetch(delegatePda);
+    assert.ok(delegate.authority.equals(newAgent.publicKey));
+    assert.equal(delegate.uses, 1);
+
+    // Old key can no longer act
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.001), null)
+        .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed with rotated-out key");
+    } catch (error: any) {
+      assert.include(error.message, "InvalidSession");
+    }
+  });
+
+  it("P8. Sub-delegate spends against its parent and dies with it", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER | CAN_SUBDELEGATE,
+        delegateDailyLimit: 0.1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    const worker = Keypair.generate();
+    const childNonce = new anchor.BN(Math.floor(Math.random() * 1_000_000));
+    const [childPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("delegate"),
+        vaultPda.toBuffer(),
+        childNonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    // Broader-than-parent scope is rejected
+    try {
+      await program.methods
+        .issueSubdelegate(
+          vaultNonce,
+          delegateNonce,
+          childNonce,
+          worker.publicKey,
+          CAN_TRANSFER | CAN_SWAP,
+          SOL(0.05),
+          0,
+          new anchor.BN(3600)
+        )
+        .accounts({
+          payer: guardian.publicKey,
+          delegateKey: agent.publicKey,
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          parentDelegate: delegatePda,
+          delegate: childPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed due to scope wider than parent");
+    } catch (error: any) {
+      assert.include(error.message, "InvalidSubdelegateScope");
+    }
+
+    await program.methods
+      .issueSubdelegate(
+        vaultNonce,
+        delegateNonce,
+        childNonce,
+        worker.publicKey,
+        CAN_TRANSFER,
+        SOL(0.05),
+        0,
+        new anchor.BN(3600)
+      )
+      .accounts({
+        payer: guardian.publicKey,
+        delegateKey: agent.publicKey,
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        parentDelegate: delegatePda,
+        delegate: childPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([agent])
+      .rpc();
+
+    await program.methods
+      .executeTransfer(vaultNonce, childNonce, SOL(0.01), null)
+      .accounts({
+        ...transferAccounts(vaultPda, childPda, worker.publicKey),
+        parentDelegate: delegatePda,
+      })
+      .signers([worker])
+      .rpc();
+
+    const parent = await program.account.delegate.fetch(delegatePda);
+    assert.ok(parent.dailySpent.eq(SOL(0.01)));
+
+    // Narrowing the parent narrows the child with it
+    await program.methods
+      .updateDelegate(vaultNonce, delegateNonce, CAN_SUBDELEGATE, null, null, null, null, false)
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, childNonce, SOL(0.01), null)
+        .accounts({
+          ...transferAccounts(vaultPda, childPda, worker.publicKey),
+          parentDelegate: delegatePda,
+        })
+        .signers([worker])
+        .rpc();
+      assert.fail("Should have failed due to the parent losing CAN_TRANSFER");
+    } catch (error: any) {
+      assert.include(error.message, "PermissionDenied");
+    }
+
+    // Revoking the parent cuts off the child
+    await program.methods
+      .delegateControl(vaultNonce, delegateNonce, 0)
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, childNonce, SOL(0.01), null)
+        .accounts({
+          ...transferAccounts(vaultPda, childPda, worker.publicKey),
+          parentDelegate: delegatePda,
+        })
+        .signers([worker])
+        .rpc();
+      assert.fail("Should have failed due to revoked parent");
+    } catch (error: any) {
+      assert.include(error.message, "DelegateInactive");
+    }
+
+    // Closing the revoked parent and reissuing it at the same nonce doesn't revive the child
+    const cranker = Keypair.generate();
+    await program.methods
+      .crankCloseDelegate(vaultNonce, delegateNonce)
+      .accounts({
+        cranker: cranker.publicKey,
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+      })
+      .signers([cranker])
+      .rpc();
+
+    await program.methods
+      .issueDelegate(
+        vaultNonce,
+        delegateNonce,
+        agent.publicKey,
+        CAN_TRANSFER | CAN_SUBDELEGATE,
+        SOL(0.1),
+        0,
+        new anchor.BN(86400)
+      )
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, childNonce, SOL(0.01), null)
+        .accounts({
+          ...transferAccounts(vaultPda, childPda, worker.publicKey),
+          parentDelegate: delegatePda,
+        })
+        .signers([worker])
+        .rpc();
+      assert.fail("Should have failed due to reissued parent");
+    } catch (error: any) {
+      assert.include(error.message, "InvalidParentDelegate");
+    }
+  });
+
+  it("P9. Relayer executes a delegate-signed intent", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    const intent = {
+      vault: vaultPda,
+      delegate: delegatePda,
+      route: {
+        transfer: { amount: SOL(0.001), destination: destination.publicKey },
+      },
+      nonce: new anchor.BN(1),
+      deadline: new anchor.BN(Math.floor(Date.now() / 1000) + 300),
+    };
+    const message = Buffer.concat([
+      Buffer.from("fuin:intent:v1"),
+      program.coder.types.encode("intent", intent),
+    ]);
+
+    // The agent only signs the message, the guardian wallet relays it
+    const verifyIx = Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: agent.secretKey,
+      message,
+    });
+
+    const destBefore = await provider.connection.getBalance(
+      destination.publicKey
+    );
+
+    await program.methods
+      .executeIntent(vaultNonce, delegateNonce, intent)
+      .accounts({
+        relayer: guardian.publicKey,
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+        destination: destination.publicKey,
+        instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([verifyIx])
+      .rpc();
+
+    const destAfter = await provider.connection.getBalance(
+      destination.publicKey
+    );
+    assert.equal(destAfter - destBefore, 0.001 * LAMPORTS_PER_SOL);
+
+    // Replaying the same signed intent is rejected. The compute budget
+    // instruction only makes the outer transaction differ from the first one.
+    try {
+      await program.methods
+        .executeIntent(vaultNonce, delegateNonce, intent)
+        .accounts({
+          relayer: guardian.publicKey,
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          delegate: delegatePda,
+          destination: destination.publicKey,
+          instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 }),
+          verifyIx,
+        ])
+        .rpc();
+      assert.fail("Should have failed due to reused nonce");
+    } catch (error: any) {
+      assert.include(error.message, "IntentNonceReused");
+    }
+  });
+
+  it("P10. Gas tank reimburses the relayer per call", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    const [gasTankPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("gas_tank"), vaultPda.toBuffer()],
+      program.programId
+    );
+    const feePerCall = 10_000;
+
+    await program.methods
+      .initGasTank(vaultNonce, new anchor.BN(feePerCall), new anchor.BN(feePerCall))
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    await program.methods
+      .fundGasTank(vaultNonce, new anchor.BN(0.01 * LAMPORTS_PER_SOL))
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    const tankBefore = await provider.connection.getBalance(gasTankPda);
+
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.001), null)
+      .accounts({
+        ...transferAccounts(vaultPda, delegatePda, agent.publicKey),
+        gasTank: gasTankPda,
+      })
+      .signers([agent])
+      .rpc();
+
+    const tankAfter = await provider.connection.getBalance(gasTankPda);
+    assert.equal(tankBefore - tankAfter, feePerCall);
+
+    // Daily cap only covers a single call
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.001), null)
+        .accounts({
+          ...transferAccounts(vaultPda, delegatePda, agent.publicKey),
+          gasTank: gasTankPda,
+        })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed due to gas tank daily cap");
+    } catch (error: any) {
+      assert.include(error.message, "DailyLimitExceeded");
+    }
+
+    await program.methods
+      .closeGasTank(vaultNonce)
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda, gasTank: gasTankPda })
+      .rpc();
+  });
+
+  it("P11. Relayer allow list blocks unknown relayers", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    // Only some other relayer is allowed, so the guardian wallet can't relay
+    await program.methods
+      .setRelayerPolicy(vaultNonce, [Keypair.generate().publicKey])
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.001), null)
+        .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed due to relayer policy");
+    } catch (error: any) {
+      assert.include(error.message, "RelayerNotAllowed");
+    }
+
+    await program.methods
+      .setRelayerPolicy(vaultNonce, [guardian.publicKey])
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.001), null)
+      .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+      .signers([agent])
+      .rpc();
+  });
+
+  it("P12. Spend ledger records executions", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    const [spendLedgerPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("spend_ledger"), vaultPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initSpendLedger(vaultNonce)
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    let vault = await program.account.vault.fetch(vaultPda);
+    assert.ok(vault.spendLedger.equals(spendLedgerPda));
+
+    // Once registered, an execution can't leave the ledger out
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.002), null)
+        .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed without the spend ledger");
+    } catch (error: any) {
+      assert.include(error.message, "MissingSpendLedger");
+    }
+
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.002), null)
+      .accounts({
+        ...transferAccounts(vaultPda, delegatePda, agent.publicKey),
+        spendLedger: spendLedgerPda,
+      })
+      .signers([agent])
+      .rpc();
+
+    const ledger = await program.account.spendLedger.fetch(spendLedgerPda);
+    assert.equal(ledger.len, 1);
+    assert.ok(ledger.entries[0].amount.eq(SOL(0.002)));
+    assert.ok(ledger.entries[0].delegate.equals(delegatePda));
+    assert.ok(ledger.entries[0].destination.equals(destination.publicKey));
+    // SOL transfers aren't valued, the entry says so instead of claiming an oracle
+    assert.ok(ledger.entries[0].usdValue.isZero());
+    assert.equal(ledger.entries[0].oracleKind, ORACLE_KIND_UNPRICED);
+
+    await program.methods
+      .closeSpendLedger(vaultNonce)
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    vault = await program.account.vault.fetch(vaultPda);
+    assert.isNull(vault.spendLedger);
+  });
+
+  it("P13. Vault cannot be drained below rent exemption", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    const vaultInfo = await provider.connection.getAccountInfo(vaultPda);
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(
+        vaultInfo!.data.length
+      );
+    const spendable = vaultInfo!.lamports - rentExempt;
+
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, delegateNonce, new anchor.BN(spendable + 1), null)
+        .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed, vault would drop below rent exemption");
+    } catch (error: any) {
+      assert.include(error.message, "InsufficientFunds");
+    }
+
+    try {
+      await program.methods
+        .withdraw(vaultNonce, new anchor.BN(spendable + 1))
+        .accounts({
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Should have failed, vault would drop below rent exemption");
+    } catch (error: any) {
+      assert.include(error.message, "InsufficientFunds");
+    }
+
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, new anchor.BN(spendable), null)
+      .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+      .signers([agent])
+      .rpc();
+
+    const balance = await provider.connection.getBalance(vaultPda);
+    assert.equal(balance, rentExempt);
+  });
+
+  it("P14. Deposits are credited to the vault and tracked per depositor", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+
+    const [contributionPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("contribution"),
+        vaultPda.toBuffer(),
+        guardian.publicKey.toBuffer(),
+        PublicKey.default.toBuffer(),
+      ],
+      program.programId
+    );
+
+    const before = await provider.connection.getBalance(vaultPda);
+
+    for (let i = 0; i < 2; i++) {
+      await program.methods
+        .depositSol(vaultNonce, SOL(0.001))
+        .accounts({
+          depositor: guardian.publicKey,
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          contribution: contributionPda,
+        })
+        .rpc();
+    }
+
+    const after = await provider.connection.getBalance(vaultPda);
+    assert.equal(after - before, 0.002 * LAMPORTS_PER_SOL);
+
+    const contribution = await program.account.contribution.fetch(contributionPda);
+    assert.ok(contribution.vault.equals(vaultPda));
+    assert.ok(contribution.depositor.equals(guardian.publicKey));
+    assert.ok(contribution.totalDeposited.eq(SOL(0.002)));
+    assert.equal(contribution.deposits, 2);
+
+    // Tracking is optional
+    await program.methods
+      .depositSol(vaultNonce, SOL(0.001))
+      .accounts({
+        depositor: guardian.publicKey,
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        contribution: null,
+      })
+      .rpc();
+  });
+
+  it("P15. Guardian sets the mint allow and deny lists", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_SWAP,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+
+    const allowed = Keypair.generate().publicKey;
+    const denied = Keypair.generate().publicKey;
+
+    await program.methods
+      .setMintPolicy(vaultNonce, [allowed], [denied])
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    const vault = await program.account.vault.fetch(vaultPda);
+    assert.ok(vault.policies.mints.allowList[0].equals(allowed));
+    assert.ok(vault.policies.mints.denyList[0].equals(denied));
+
+    const tooMany = Array.from({ length: 9 }, () => Keypair.generate().publicKey);
+    try {
+      await program.methods
+        .setMintPolicy(vaultNonce, [], tooMany)
+        .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+        .rpc();
+      assert.fail("Should have failed due to deny list size");
+    } catch (error: any) {
+      assert.include(error.message, "WhitelistFull");
+    }
+  });
+
+  it("P16. Transfers above the memo threshold need a memo", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    await program.methods
+      .setRiskPolicy(vaultNonce, 0, new anchor.BN(0), SOL(0.001))
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    // At the threshold no memo is needed
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.001), null)
+      .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+      .signers([agent])
+      .rpc();
+
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, delegateNonce, SOL(0.002), null)
+        .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed without a memo");
+    } catch (error: any) {
+      assert.include(error.message, "MemoRequired");
+    }
+
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.002), "INV-0042")
+      .accounts({
+        ...transferAccounts(vaultPda, delegatePda, agent.publicKey),
+        memoProgram: MEMO_PROGRAM_ID,
+      })
+      .signers([agent])
+      .rpc();
+  });
+
+  it("P17. Batch pays several recipients as one configurable use", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.005 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 3,
+      });
+
+    const recipients = [Keypair.generate().publicKey, Keypair.generate().publicKey];
+    const batchAccounts = {
+      relayer: guardian.publicKey,
+      delegateKey: agent.publicKey,
+      guardian: guardian.publicKey,
+      vault: vaultPda,
+      delegate: delegatePda,
+      tokenProgram: null,
+    };
+    const destinations = recipients.map((pubkey) => ({
+      pubkey,
+      isSigner: false,
+      isWritable: true,
+    }));
+
+    // Each item is still held to the per-tx cap, and nothing is paid if one fails
+    try {
+      await program.methods
+        .executeBatch(vaultNonce, delegateNonce, [
+          { sol: { amount: SOL(0.001) } },
+          { sol: { amount: SOL(0.01) } },
+        ])
+        .accounts(batchAccounts)
+        .remainingAccounts(destinations)
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed due to per-tx cap");
+    } catch (error: any) {
+      assert.include(error.message, "PerTxLimitExceeded");
+    }
+    assert.equal(await provider.connection.getBalance(recipients[0]), 0);
+
+    await program.methods
+      .updateVault(vaultNonce, null, null, null, null, 1, null, null)
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    await program.methods
+      .executeBatch(vaultNonce, delegateNonce, [
+        { sol: { amount: SOL(0.002) } },
+        { sol: { amount: SOL(0.003) } },
+      ])
+      .accounts(batchAccounts)
+      .remainingAccounts(destinations)
+      .signers([agent])
+      .rpc();
+
+    assert.equal(await provider.connection.getBalance(recipients[0]), 0.002 * LAMPORTS_PER_SOL);
+    assert.equal(await provider.connection.getBalance(recipients[1]), 0.003 * LAMPORTS_PER_SOL);
+
+    const delegate = await program.account.delegate.fetch(delegatePda);
+    assert.equal(delegate.uses, 1);
+    assert.ok(delegate.dailySpent.eq(SOL(0.005)));
+  });
+
+  it("P18. Anyone cranks a due scheduled payment, once per interval", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+
+    const scheduleNonce = new anchor.BN(1);
+    const [schedulePda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("schedule"),
+        vaultPda.toBuffer(),
+        scheduleNonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const recipient = Keypair.generate().publicKey;
+
+    // Starts now, hourly, at most two payments
+    await program.methods
+      .createSchedule(vaultNonce, scheduleNonce, recipient, null, SOL(0.001), new anchor.BN(3600), new anchor.BN(0), new anchor.BN(0), 2)
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda, schedule: schedulePda })
+      .rpc();
+
+    // Any signer can crank, no delegate involved
+    const cranker = Keypair.generate();
+    const crankAccounts = {
+      cranker: cranker.publicKey,
+      guardian: guardian.publicKey,
+      vault: vaultPda,
+      schedule: schedulePda,
+      destination: recipient,
+      mint: null,
+      vaultTokenAccount: null,
+      tokenProgram: null,
+    };
+
+    await program.methods
+      .crankSchedule(vaultNonce, scheduleNonce)
+      .accounts(crankAccounts)
+      .signers([cranker])
+      .rpc();
+
+    assert.equal(await provider.connection.getBalance(recipient), 0.001 * LAMPORTS_PER_SOL);
+    const schedule = await program.account.schedule.fetch(schedulePda);
+    assert.equal(schedule.paymentsMade, 1);
+
+    try {
+      await program.methods
+        .crankSchedule(vaultNonce, scheduleNonce)
+        .accounts(crankAccounts)
+        .signers([cranker])
+        .rpc();
+      assert.fail("Should have failed, next payment isn't due");
+    } catch (error: any) {
+      assert.include(error.message, "ScheduleNotDue");
+    }
+
+    await program.methods
+      .cancelSchedule(vaultNonce, scheduleNonce)
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda, schedule: schedulePda })
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(schedulePda));
+  });
+
+  it("P19. Streamed lamports are committed and can't be spent by delegates", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    const streamNonce = new anchor.BN(1);
+    const [streamPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("stream"),
+        vaultPda.toBuffer(),
+        streamNonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const recipient = Keypair.generate();
+
+    // 0.15 SOL over 3000 seconds, starting now
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .createStream(vaultNonce, streamNonce, recipient.publicKey, new anchor.BN(50_000), new anchor.BN(0), new anchor.BN(now + 3000))
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda, stream: streamPda })
+      .rpc();
+
+    let vault = await program.account.vault.fetch(vaultPda);
+    const committed = vault.committedLamports.toNumber();
+    assert.isAbove(committed, 0);
+
+    const vaultInfo = await provider.connection.getAccountInfo(vaultPda);
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(
+        vaultInfo!.data.length
+      );
+    const available = vaultInfo!.lamports - rentExempt - committed;
+
+    try {
+      await program.methods
+        .executeTransfer(vaultNonce, delegateNonce, new anchor.BN(available + 1), null)
+        .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed, the stream's lamports are committed");
+    } catch (error: any) {
+      assert.include(error.message, "InsufficientFunds");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    const withdrawAccounts = {
+      recipient: recipient.publicKey,
+      guardian: guardian.publicKey,
+      vault: vaultPda,
+      stream: streamPda,
+    };
+
+    // A few seconds of vesting is far below rent exemption for an empty wallet
+    try {
+      await program.methods
+        .withdrawStream(vaultNonce, streamNonce)
+        .accounts(withdrawAccounts)
+        .signers([recipient])
+        .rpc();
+      assert.fail("Should have failed, the recipient would be left below rent exemption");
+    } catch (error: any) {
+      assert.include(error.message, "RecipientNotRentExempt");
+    }
+
+    const recipientRent =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    await fundAccount(provider, recipient.publicKey, recipientRent);
+
+    await program.methods
+      .withdrawStream(vaultNonce, streamNonce)
+      .accounts(withdrawAccounts)
+      .signers([recipient])
+      .rpc();
+
+    const stream = await program.account.stream.fetch(streamPda);
+    assert.isAbove(stream.withdrawn.toNumber(), 0);
+    assert.equal(
+      await provider.connection.getBalance(recipient.publicKey),
+      recipientRent + stream.withdrawn.toNumber()
+    );
+
+    // Cancelling pays out what vested and releases the rest
+    await program.methods
+      .cancelStream(vaultNonce, streamNonce)
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        stream: streamPda,
+        recipient: recipient.publicKey,
+      })
+      .rpc();
+
+    vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(vault.committedLamports.toNumber(), 0);
+    assert.isNull(await provider.connection.getAccountInfo(streamPda));
+  });
+
+  it("P20. Guardian withdraws SPL tokens from the vault", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+    const a = await fundedVaultMint(vaultPda, 1_000_000);
+    const b = await fundedVaultMint(vaultPda, 1_000_000);
+    // Holds mint A but belongs to the guardian
+    const foreign = await createAccount(provider.connection, guardian.payer, a.mint, guardian.publicKey, Keypair.generate());
+
+    await program.methods
+      .withdrawSpl(vaultNonce, new anchor.BN(400_000))
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        vaultTokenAccount: a.vaultAta,
+        destinationTokenAccount: a.guardianAta,
+        mint: a.mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    assert.equal(Number((await getAccount(provider.connection, a.vaultAta)).amount), 600_000);
+    assert.equal(Number((await getAccount(provider.connection, a.guardianAta)).amount), 400_000);
+
+    // Vault token account of another mint
+    try {
+      await program.methods
+        .withdrawSpl(vaultNonce, new anchor.BN(1))
+        .accounts({
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: b.vaultAta,
+          destinationTokenAccount: a.guardianAta,
+          mint: a.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("Should have failed due to mint mismatch");
+    } catch (error: any) {
+      assert.include(error.message, "MintMismatch");
+    }
+
+    // Source the vault doesn't own
+    try {
+      await program.methods
+        .withdrawSpl(vaultNonce, new anchor.BN(1))
+        .accounts({
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          vaultTokenAccount: foreign,
+          destinationTokenAccount: a.guardianAta,
+          mint: a.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("Should have failed due to foreign owner");
+    } catch (error: any) {
+      assert.include(error.message, "VaultOwnerMismatch");
+    }
+  });
+
+  it("P21. Batched SPL withdrawal validates every remaining account", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+    const a = await fundedVaultMint(vaultPda, 1_000_000);
+    const b = await fundedVaultMint(vaultPda, 1_000_000);
+    const c = await fundedVaultMint(vaultPda, 1_000_000, TOKEN_2022_PROGRAM_ID);
+    const foreign = await createAccount(provider.connection, guardian.payer, a.mint, guardian.publicKey, Keypair.generate());
+
+    const entry = (source: PublicKey, mint: PublicKey, destination: PublicKey) => [
+      { pubkey: source, isSigner: false, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: destination, isSigner: false, isWritable: true },
+    ];
+    const withdrawBatch = (
+      amounts: number[],
+      entries: { pubkey: PublicKey; isSigner: boolean; isWritable: boolean }[],
+      tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+    ) =>
+      program.methods
+        .withdrawSplBatch(vaultNonce, amounts.map((amount) => new anchor.BN(amount)))
+        .accounts({ guardian: guardian.publicKey, vault: vaultPda, tokenProgram })
+        .remainingAccounts(entries)
+        .rpc();
+
+    await withdrawBatch(
+      [250_000, 300_000],
+      [...entry(a.vaultAta, a.mint, a.guardianAta), ...entry(b.vaultAta, b.mint, b.guardianAta)]
+    );
+    assert.equal(Number((await getAccount(provider.connection, a.guardianAta)).amount), 250_000);
+    assert.equal(Number((await getAccount(provider.connection, b.guardianAta)).amount), 300_000);
+    assert.equal(Number((await getAccount(provider.connection, b.vaultAta)).amount), 700_000);
+
+    const failures: [string, () => Promise<string>][] = [
+      // One amount but two triples
+      ["InvalidBatch", () => withdrawBatch([1], [...entry(a.vaultAta, a.mint, a.guardianAta), ...entry(b.vaultAta, b.mint, b.guardianAta)])],
+      // Source holds a different mint
+      ["MintMismatch", () => withdrawBatch([1], entry(b.vaultAta, a.mint, a.guardianAta))],
+      // Destination holds a different mint
+      ["MintMismatch", () => withdrawBatch([1], entry(a.vaultAta, a.mint, b.guardianAta))],
+      // Source owned by the guardian, not the vault
+      ["VaultOwnerMismatch", () => withdrawBatch([1], entry(foreign, a.mint, a.guardianAta))],
+      // Token-2022 account passed with the legacy token program
+      ["InvalidTokenProgram", () => withdrawBatch([1], entry(c.vaultAta, c.mint, c.guardianAta))],
+    ];
+    for (const [expected, attempt] of failures) {
+      let rejected = false;
+      try {
+        await attempt();
+      } catch (error: any) {
+        rejected = true;
+        assert.include(error.message, expected);
+      }
+      assert.isTrue(rejected, `Batch should have been rejected with ${expected}`);
+    }
+
+    // A failed batch moves nothing
+    assert.equal(Number((await getAccount(provider.connection, a.vaultAta)).amount), 750_000);
+  });
+
+  it("P22. Guardian creates and closes registered vault token accounts", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+    const mint = await newMint();
+    const vaultAta = getAssociatedTokenAddressSync(mint, vaultPda, true);
+    const ataAccounts = {
+      guardian: guardian.publicKey,
+      vault: vaultPda,
+      mint,
+      vaultTokenAccount: vaultAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const createAta = () =>
+      program.methods
+        .createVaultTokenAccount(vaultNonce)
+        .accounts({ ...ataAccounts, associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID })
+        .rpc();
+
+    await createAta();
+    let vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(vault.tokenAccounts.length, 1);
+    assert.ok(vault.tokenAccounts[0].mint.equals(mint));
+    assert.ok(vault.tokenAccounts[0].address.equals(vaultAta));
+    assert.ok(vault.tokenAccounts[0].tokenProgram.equals(TOKEN_PROGRAM_ID));
+
+    // Creating it again adopts the existing account without a second entry
+    await createAta();
+    vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(vault.tokenAccounts.length, 1);
+
+    await mintTo(provider.connection, guardian.payer, mint, vaultAta, guardian.payer, 1_000);
+    try {
+      await program.methods.closeVaultTokenAccount(vaultNonce).accounts(ataAccounts).rpc();
+      assert.fail("Should have failed, the account still holds tokens");
+    } catch (error: any) {
+      assert.include(error.message, "TokenAccountNotEmpty");
+    }
+
+    const guardianAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      guardian.payer,
+      mint,
+      guardian.publicKey
+    );
+    await program.methods
+      .withdrawSpl(vaultNonce, new anchor.BN(1_000))
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        vaultTokenAccount: vaultAta,
+        destinationTokenAccount: guardianAta.address,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await program.methods.closeVaultTokenAccount(vaultNonce).accounts(ataAccounts).rpc();
+    assert.isNull(await provider.connection.getAccountInfo(vaultAta));
+    vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(vault.tokenAccounts.length, 0);
+  });
+
+  it("P23. Token account registry holds at most 16 mints", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+    const createAta = (mint: PublicKey) =>
+      program.methods
+        .createVaultTokenAccount(vaultNonce)
+        .accounts({
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          mint,
+          vaultTokenAccount: getAssociatedTokenAddressSync(mint, vaultPda, true),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    for (let i = 0; i < 16; i++) {
+      await createAta(await newMint());
+    }
+    const vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(vault.tokenAccounts.length, 16);
+
+    try {
+      await createAta(await newMint());
+      assert.fail("Should have failed, the registry is full");
+    } catch (error: any) {
+      assert.include(error.message, "TokenAccountRegistryFull");
+    }
+  });
+
+  it("P24. SPL transfers only spend from the vault's canonical token account", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+    const { mint, guardianAta } = await fundedVaultMint(vaultPda, 1_000_000);
+
+    // Vault-owned, but not the vault's associated token account
+    const stray = await createAccount(provider.connection, guardian.payer, mint, vaultPda, Keypair.generate());
+    await mintTo(provider.connection, guardian.payer, mint, stray, guardian.payer, 1_000);
+
+    try {
+      await program.methods
+        .executeSplTransfer(vaultNonce, delegateNonce, new anchor.BN(1_000), null, null)
+        .accounts({
+          relayer: guardian.publicKey,
+          delegateKey: agent.publicKey,
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          delegate: delegatePda,
+          vaultTokenAccount: stray,
+          destinationTokenAccount: guardianAta,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have failed, the source isn't the vault's ATA");
+    } catch (error: any) {
+      assert.include(error.message, "NonCanonicalTokenAccount");
+    }
+  });
+
+  it("P25. SPL deposits credit what the vault receives after transfer fees", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+
+    // Token-2022 mint withholding 1% on every transfer
+    const mint = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: guardian.publicKey,
+          newAccountPubkey: mint.publicKey,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          mint.publicKey,
+          guardian.publicKey,
+          guardian.publicKey,
+          100,
+          BigInt(1_000_000),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(mint.publicKey, 6, guardian.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [mint]
+    );
+
+    const depositorAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      guardian.payer,
+      mint.publicKey,
+      guardian.publicKey,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      guardian.payer,
+      mint.publicKey,
+      depositorAta.address,
+      guardian.payer,
+      100_000,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const [contributionPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("contribution"),
+        vaultPda.toBuffer(),
+        guardian.publicKey.toBuffer(),
+        mint.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const vaultAta = getAssociatedTokenAddressSync(mint.publicKey, vaultPda, true, TOKEN_2022_PROGRAM_ID);
+
+    await program.methods
+      .depositSpl(vaultNonce, new anchor.BN(100_000))
+      .accounts({
+        depositor: guardian.publicKey,
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        mint: mint.publicKey,
+        depositorTokenAccount: depositorAta.address,
+        vaultTokenAccount: vaultAta,
+        contribution: contributionPda,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // 1,000 withheld, only the net 99,000 is credited
+    const received = await getAccount(provider.connection, vaultAta, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(received.amount), 99_000);
+    const contribution = await program.account.contribution.fetch(contributionPda);
+    assert.equal(contribution.totalDeposited.toNumber(), 99_000);
+  });
+
+  it("P26. Executions emit decodable self-CPI events", async () => {
+    const { vaultPda, vaultNonce, delegatePda, delegateNonce, agent } =
+      await setupVaultAndDelegate({
+        dailyCap: 1 * LAMPORTS_PER_SOL,
+        perTxCap: 0.5 * LAMPORTS_PER_SOL,
+        delegatePermissions: CAN_TRANSFER,
+        delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+        delegateMaxUses: 0,
+      });
+
+    await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.01), null)
+      .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+      .signers([agent])
+      .rpc();
+    const transferSig = await program.methods
+      .executeTransfer(vaultNonce, delegateNonce, SOL(0.02), null)
+      .accounts(transferAccounts(vaultPda, delegatePda, agent.publicKey))
+      .signers([agent])
+      .rpc();
+
+    const transfer = (await cpiEvents(transferSig)).find((e) => e.name === "TransferExecuted");
+    assert.ok(transfer, "transfer event not found");
+    assert.ok(transfer.data.vault.equals(vaultPda));
+    assert.ok(transfer.data.delegate.equals(delegatePda));
+    assert.ok(transfer.data.destination.equals(destination.publicKey));
+    assert.ok(transfer.data.amount.eq(SOL(0.02)));
+    // Counters are reported after this execution is charged
+    assert.ok(transfer.data.vaultDailySpent.eq(SOL(0.03)));
+    assert.ok(transfer.data.delegateDailySpent.eq(SOL(0.03)));
+    assert.equal(transfer.data.delegateUses, 2);
+
+    const updateSig = await program.methods
+      .updateDelegate(vaultNonce, delegateNonce, null, SOL(0.5), 10, null, null, true)
+      .accounts({
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        delegate: delegatePda,
+      })
+      .rpc();
+
+    const update = (await cpiEvents(updateSig)).find((e) => e.name === "DelegateUpdated");
+    assert.ok(update, "update event not found");
+    assert.ok(update.data.vault.equals(vaultPda));
+    assert.ok(update.data.delegate.equals(delegatePda));
+    assert.equal(update.data.permissions, CAN_TRANSFER);
+    assert.ok(update.data.dailyLimit.eq(SOL(0.5)));
+    assert.equal(update.data.maxUses, 10);
+    assert.equal(update.data.uses, 0);
+  });
+
+  it("P27. SPL schedule payouts are charged against the USD caps", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+    const { mint, vaultAta } = await fundedVaultMint(vaultPda, 10_000_000);
+
+    // $1 peg, $1.50 per payment at most
+    await program.methods
+      .setPeggedMints(vaultNonce, [{ mint, usdRate: new anchor.BN(1_000_000), maxDepegBps: 0 }])
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+    await program.methods
+      .updateVault(vaultNonce, null, null, null, null, null, null, new anchor.BN(1_500_000))
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda })
+      .rpc();
+
+    const recipient = Keypair.generate().publicKey;
+    const recipientAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      guardian.payer,
+      mint,
+      recipient
+    );
+
+    const crank = async (scheduleNonce: anchor.BN, amount: number) => {
+      const [schedulePda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("schedule"),
+          vaultPda.toBuffer(),
+          scheduleNonce.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      await program.methods
+        .createSchedule(vaultNonce, scheduleNonce, recipient, mint, new anchor.BN(amount), new anchor.BN(3600), new anchor.BN(0), new anchor.BN(0), 1)
+        .accounts({ guardian: guardian.publicKey, vault: vaultPda, schedule: schedulePda })
+        .rpc();
+      return program.methods
+        .crankSchedule(vaultNonce, scheduleNonce)
+        .accounts({
+          cranker: guardian.publicKey,
+          guardian: guardian.publicKey,
+          vault: vaultPda,
+          schedule: schedulePda,
+          destination: recipientAta.address,
+          mint,
+          vaultTokenAccount: vaultAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    };
+
+    // 2 tokens is $2, over the per-payment cap
+    try {
+      await crank(new anchor.BN(1), 2_000_000);
+      assert.fail("Should have failed due to the USD per-tx cap");
+    } catch (error: any) {
+      assert.include(error.message, "UsdPerTxLimitExceeded");
+    }
+
+    await crank(new anchor.BN(2), 1_000_000);
+    const vault = await program.account.vault.fetch(vaultPda);
+    assert.ok(vault.policies.spending.dailyUsdSpent.eq(new anchor.BN(1_000_000)));
+  });
+
+  it("P28. SOL schedule payouts can't leave a new recipient below rent exemption", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+
+    const scheduleNonce = new anchor.BN(1);
+    const [schedulePda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("schedule"),
+        vaultPda.toBuffer(),
+        scheduleNonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const recipient = Keypair.generate().publicKey;
+
+    await program.methods
+      .createSchedule(vaultNonce, scheduleNonce, recipient, null, new anchor.BN(1_000), new anchor.BN(3600), new anchor.BN(0), new anchor.BN(0), 1)
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda, schedule: schedulePda })
+      .rpc();
+
+    const crankAccounts = {
+      cranker: guardian.publicKey,
+      guardian: guardian.publicKey,
+      vault: vaultPda,
+      schedule: schedulePda,
+      destination: recipient,
+      mint: null,
+      vaultTokenAccount: null,
+      tokenProgram: null,
+    };
+
+    try {
+      await program.methods
+        .crankSchedule(vaultNonce, scheduleNonce)
+        .accounts(crankAccounts)
+        .rpc();
+      assert.fail("Should have failed, the recipient account doesn't exist yet");
+    } catch (error: any) {
+      assert.include(error.message, "RecipientNotRentExempt");
+    }
+
+    // Once the recipient holds its rent the payment goes through
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    await fundAccount(provider, recipient, rent);
+    await program.methods
+      .crankSchedule(vaultNonce, scheduleNonce)
+      .accounts(crankAccounts)
+      .rpc();
+    assert.equal(await provider.connection.getBalance(recipient), rent + 1_000);
+  });
+
+  it("P29. Cancelling a stream to an unfunded recipient keeps the vested amount claimable", async () => {
+    const { vaultPda, vaultNonce } = await setupVaultAndDelegate({
+      dailyCap: 1 * LAMPORTS_PER_SOL,
+      perTxCap: 0.5 * LAMPORTS_PER_SOL,
+      delegatePermissions: CAN_TRANSFER,
+      delegateDailyLimit: 1 * LAMPORTS_PER_SOL,
+      delegateMaxUses: 0,
+    });
+
+    const streamNonce = new anchor.BN(1);
+    const [streamPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("stream"),
+        vaultPda.toBuffer(),
+        streamNonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const recipient = Keypair.generate();
+
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .createStream(vaultNonce, streamNonce, recipient.publicKey, new anchor.BN(50_000), new anchor.BN(0), new anchor.BN(now + 3000))
+      .accounts({ guardian: guardian.publicKey, vault: vaultPda, stream: streamPda })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    const cancelAccounts = {
+      guardian: guardian.publicKey,
+      vault: vaultPda,
+      stream: streamPda,
+      recipient: recipient.publicKey,
+    };
+
+    // The vested few seconds can't be paid to an empty wallet, the cancel still goes through
+    await program.methods
+      .cancelStream(vaultNonce, streamNonce)
+      .accounts(cancelAccounts)
+      .rpc();
+
+    // Vesting stopped at the cancel, only the vested amount is still committed
+    const stream = await program.account.stream.fetch(streamPda);
+    const claimable = stream.ratePerSecond
+      .mul(stream.endTime.sub(stream.startTime))
+      .toNumber();
+    assert.isAbove(claimable, 0);
+    assert.isBelow(stream.endTime.toNumber(), now + 3000);
+    let vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(vault.committedLamports.toNumber(), claimable);
+    assert.equal(await provider.connection.getBalance(recipient.publicKey), 0);
+
+    // Once funded, the recipient claims it
+    const recipientRent =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    await fundAccount(provider, recipient.publicKey, recipientRent);
+    await program.methods
+      .withdrawStream(vaultNonce, streamNonce)
+      .accounts({
+        recipient: recipient.publicKey,
+        guardian: guardian.publicKey,
+        vault: vaultPda,
+        stream: streamPda,
+      })
+      .signers([recipient])
+      .rpc();
+
+    assert.equal(
+      await provider.connection.getBalance(recipient.publicKey),
+      recipientRent + claimable
+    );
+    vault = await program.account.vault.fetch(vaultPda);
+    assert.equal(vault.committedLamports.toNumber(), 0);
+
+    // Nothing left to pay, cancelling again closes the stream
+    await program.methods
+      .cancelStream(vaultNonce, streamNonce)
+      .accounts(cancelAccounts)
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(streamPda));
+  });
 });